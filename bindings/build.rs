@@ -17,6 +17,8 @@ fn main() {
         Windows::Win32::System::Threading::GetCurrentThreadId,
         Windows::Win32::System::Threading::AttachThreadInput,
         Windows::Win32::System::Threading::GetCurrentProcessId,
+        Windows::Win32::UI::HiDpi::GetDpiForMonitor,
+        Windows::Win32::UI::HiDpi::MONITOR_DPI_TYPE,
         Windows::Win32::UI::KeyboardAndMouseInput::SetFocus,
         Windows::Win32::UI::Accessibility::SetWinEventHook,
         Windows::Win32::UI::Accessibility::HWINEVENTHOOK,
@@ -8,24 +8,37 @@ use ::std::convert::Into;
 use ::std::iter::Extend;
 use ::std::iter::Iterator;
 use ::std::matches;
+use ::std::result::Result::Err;
+use ::std::result::Result::Ok;
 use ::std::string::ToString;
 use ::std::unreachable;
 
 use ::quote::quote;
 use ::std::option::Option::Some;
 use ::syn::parse_macro_input;
+use ::syn::Attribute;
 use ::syn::Data;
 use ::syn::DataEnum;
 use ::syn::DeriveInput;
+use ::syn::Error;
 use ::syn::Fields;
 use ::syn::FieldsNamed;
 use ::syn::FieldsUnnamed;
+use ::syn::Meta;
+use ::syn::MetaList;
+use ::syn::NestedMeta;
 
-#[proc_macro_derive(AhkFunction)]
+#[proc_macro_derive(AhkFunction, attributes(ahk))]
 pub fn ahk_function(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
 
+    // #[ahk(async)] generates a blocking RunWait function alongside an Async variant using Run
+    let generate_async = match parse_ahk_attributes(&input.attrs) {
+        Ok(generate_async) => generate_async,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
     match input.data {
         Data::Struct(s) => match s.fields {
             Fields::Named(FieldsNamed { named, .. }) => {
@@ -39,19 +52,45 @@ pub fn ahk_function(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStre
                     .replace("% ", "%")
                     .replace("%%", "% %");
 
-                quote! {
-                    impl AhkFunction for #name {
-                        fn generate_ahk_function() -> String {
-                            ::std::format!(r#"
+                if generate_async {
+                    quote! {
+                        impl AhkFunction for #name {
+                            fn generate_ahk_function() -> String {
+                                ::std::format!(r#"
 {}({}) {{ 
+    RunWait, komorebic.exe {} {}, , Hide 
+}}
+
+{}Async({}) {{ 
     Run, komorebic.exe {} {}, , Hide 
 }}"#, 
-                                ::std::stringify!(#name),
-                                #arguments,
-                                stringify!(#name).to_kebab_case(),
-                                #called_arguments
-                            )
-                       }
+                                    ::std::stringify!(#name),
+                                    #arguments,
+                                    stringify!(#name).to_kebab_case(),
+                                    #called_arguments,
+                                    ::std::stringify!(#name),
+                                    #arguments,
+                                    stringify!(#name).to_kebab_case(),
+                                    #called_arguments
+                                )
+                           }
+                        }
+                    }
+                } else {
+                    quote! {
+                        impl AhkFunction for #name {
+                            fn generate_ahk_function() -> String {
+                                ::std::format!(r#"
+{}({}) {{ 
+    Run, komorebic.exe {} {}, , Hide 
+}}"#, 
+                                    ::std::stringify!(#name),
+                                    #arguments,
+                                    stringify!(#name).to_kebab_case(),
+                                    #called_arguments
+                                )
+                           }
+                        }
                     }
                 }
             }
@@ -62,6 +101,35 @@ pub fn ahk_function(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStre
     .into()
 }
 
+// Anything other than #[ahk(async)] is a compile error, so that a typo doesn't silently generate
+// the wrong function
+fn parse_ahk_attributes(attrs: &[Attribute]) -> ::syn::Result<bool> {
+    let mut generate_async = false;
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("ahk")) {
+        match attr.parse_meta()? {
+            Meta::List(MetaList { nested, .. }) => {
+                for meta in &nested {
+                    match meta {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("async") => {
+                            generate_async = true;
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(
+                                meta,
+                                "unknown ahk argument, expected `async`",
+                            ));
+                        }
+                    }
+                }
+            }
+            meta => return Err(Error::new_spanned(meta, "expected #[ahk(async)]")),
+        }
+    }
+
+    Ok(generate_async)
+}
+
 #[proc_macro_derive(AhkLibrary)]
 pub fn ahk_library(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -118,3 +186,100 @@ pub fn ahk_library(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStrea
     }
     .into()
 }
+
+#[proc_macro_derive(PowerShellFunction)]
+pub fn powershell_function(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+
+    match input.data {
+        Data::Struct(s) => match s.fields {
+            Fields::Named(FieldsNamed { named, .. }) => {
+                let variables = named
+                    .iter()
+                    .filter_map(|f| f.ident.as_ref())
+                    .map(|ident| ::std::format!("${}", ident))
+                    .collect::<::std::vec::Vec<_>>();
+
+                let arguments = variables.join(", ");
+                let called_arguments = variables.join(" ");
+
+                quote! {
+                    impl PowerShellFunction for #name {
+                        fn generate_powershell_function() -> String {
+                            ::std::format!(r#"
+function Invoke-Komorebic{}({}) {{
+    komorebic.exe {} {}
+}}"#,
+                                ::std::stringify!(#name),
+                                #arguments,
+                                stringify!(#name).to_kebab_case(),
+                                #called_arguments
+                            )
+                       }
+                    }
+                }
+            }
+            _ => unreachable!("only to be used on structs with named fields"),
+        },
+        _ => unreachable!("only to be used on structs"),
+    }
+    .into()
+}
+
+#[proc_macro_derive(PowerShellLibrary)]
+pub fn powershell_library(input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = input.ident;
+
+    match input.data {
+        Data::Enum(DataEnum { variants, .. }) => {
+            let enums = variants.iter().filter(|&v| {
+                matches!(v.fields, Fields::Unit) || matches!(v.fields, Fields::Unnamed(..))
+            });
+
+            let mut stream = ::proc_macro2::TokenStream::new();
+
+            for variant in enums.clone() {
+                match &variant.fields {
+                    Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
+                        for field in unnamed {
+                            stream.extend(quote! {
+                                v.push(#field::generate_powershell_function());
+                            });
+                        }
+                    }
+                    Fields::Unit => {
+                        let name = &variant.ident;
+                        stream.extend(quote! {
+                            v.push(::std::format!(r#"
+function Invoke-Komorebic{}() {{
+    komorebic.exe {}
+}}"#,
+                                ::std::stringify!(#name),
+                                ::std::stringify!(#name).to_kebab_case()
+                            ));
+                        });
+                    }
+                    Fields::Named(_) => {
+                        unreachable!("only to be used with unnamed and unit fields");
+                    }
+                }
+            }
+
+            quote! {
+                impl #name {
+                    fn generate_powershell_library() -> String {
+                        let mut v: Vec<String> = vec![String::from("# Generated by komorebic.exe")];
+
+                        #stream
+
+                        v.join("\n")
+                    }
+               }
+            }
+        }
+        _ => unreachable!("only to be used on enums"),
+    }
+    .into()
+}
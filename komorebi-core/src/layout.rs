@@ -1,6 +1,18 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
 use std::num::NonZeroUsize;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use clap::ArgEnum;
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
 use serde::Deserialize;
 use serde::Serialize;
 use strum::Display;
@@ -10,12 +22,72 @@ use crate::OperationDirection;
 use crate::Rect;
 use crate::Sizing;
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ArgEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize, Display)]
 #[strum(serialize_all = "snake_case")]
 pub enum Layout {
     BSP,
-    Columns,
+    Columns {
+        main_ratio: u32,
+    },
     Rows,
+    ThreeColumn {
+        main_ratio: u32,
+        secondary_ratio: u32,
+    },
+    Deck,
+    Custom(String),
+    Composite {
+        primary: Box<Self>,
+        secondary: Box<Self>,
+        primary_count: usize,
+    },
+}
+
+// ArgEnum can't be derived for enums with data-carrying variants, so layouts with configurable
+// ratios are given their default values when selected from the command line, and the path of the
+// executable for a custom layout and the areas of a composite layout have to be filled in from
+// separate arguments
+impl ArgEnum for Layout {
+    const VARIANTS: &'static [&'static str] = &[
+        "bsp",
+        "columns",
+        "rows",
+        "three-column",
+        "deck",
+        "custom",
+        "composite",
+    ];
+
+    fn from_str(input: &str, case_insensitive: bool) -> Result<Self, String> {
+        let func = if case_insensitive {
+            str::eq_ignore_ascii_case
+        } else {
+            str::eq
+        };
+
+        match input {
+            val if func(val, "bsp") => Ok(Self::BSP),
+            val if func(val, "columns") => Ok(Self::default_columns()),
+            val if func(val, "rows") => Ok(Self::Rows),
+            val if func(val, "three-column") => Ok(Self::default_three_column()),
+            val if func(val, "deck") => Ok(Self::Deck),
+            val if func(val, "custom") => Ok(Self::Custom(String::new())),
+            val if func(val, "composite") => Ok(Self::default_composite()),
+            e => Err(format!("Invalid variant: {}", e)),
+        }
+    }
+
+    fn as_arg(&self) -> Option<&'static str> {
+        match self {
+            Self::BSP => Some("bsp"),
+            Self::Columns { .. } => Some("columns"),
+            Self::Rows => Some("rows"),
+            Self::ThreeColumn { .. } => Some("three-column"),
+            Self::Deck => Some("deck"),
+            Self::Custom(_) => Some("custom"),
+            Self::Composite { .. } => Some("composite"),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ArgEnum)]
@@ -27,6 +99,51 @@ pub enum Flip {
 }
 
 impl Layout {
+    #[must_use]
+    pub const fn default_columns() -> Self {
+        Self::Columns { main_ratio: 50 }
+    }
+
+    #[must_use]
+    pub const fn default_three_column() -> Self {
+        Self::ThreeColumn {
+            main_ratio: 2,
+            secondary_ratio: 1,
+        }
+    }
+
+    // The path of a custom layout is filled in from a separate argument on the command line, so a
+    // layout that was parsed on its own can still be missing it
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Custom(path) if path.is_empty() => Err(anyhow!(
+                "a path to an executable must be given for the custom layout"
+            )),
+            Self::Composite {
+                primary, secondary, ..
+            } => {
+                primary.validate()?;
+                secondary.validate()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Every thread that calculates layouts has its own cache, so this bumps a generation counter
+    // which makes each of them drop their entries the next time they are used
+    pub fn clear_custom_layout_cache() {
+        CUSTOM_LAYOUT_CACHE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[must_use]
+    pub fn default_composite() -> Self {
+        Self::Composite {
+            primary: Box::new(Self::Rows),
+            secondary: Box::new(Self::Rows),
+            primary_count: 1,
+        }
+    }
+
     #[must_use]
     #[allow(clippy::cast_precision_loss)]
     pub fn resize(
@@ -130,16 +247,16 @@ impl Layout {
         }
     }
 
-    #[must_use]
     #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
     pub fn calculate(
         &self,
         area: &Rect,
         len: NonZeroUsize,
         container_padding: Option<i32>,
+        gap: i32,
         layout_flip: Option<Flip>,
         resize_dimensions: &[Option<Rect>],
-    ) -> Vec<Rect> {
+    ) -> Result<Vec<Rect>> {
         let len = usize::from(len);
         let mut dimensions = match self {
             Layout::BSP => recursive_fibonacci(
@@ -149,24 +266,7 @@ impl Layout {
                 layout_flip,
                 calculate_resize_adjustments(resize_dimensions),
             ),
-            Layout::Columns => {
-                let right = area.right / len as i32;
-                let mut left = 0;
-
-                let mut layouts: Vec<Rect> = vec![];
-                for _ in 0..len {
-                    layouts.push(Rect {
-                        left: area.left + left,
-                        top: area.top,
-                        right,
-                        bottom: area.bottom,
-                    });
-
-                    left += right;
-                }
-
-                layouts
-            }
+            Layout::Columns { main_ratio } => columns(area, len, *main_ratio),
             Layout::Rows => {
                 let bottom = area.bottom / len as i32;
                 let mut top = 0;
@@ -185,14 +285,384 @@ impl Layout {
 
                 layouts
             }
+            Layout::ThreeColumn {
+                main_ratio,
+                secondary_ratio,
+            } => three_column(area, len, *main_ratio, *secondary_ratio, layout_flip),
+            // Every container gets the whole work area, and the focused container is brought to
+            // the top when focus changes
+            Layout::Deck => vec![*area; len],
+            Self::Custom(path) => custom(path, area, len)?,
+            Self::Composite {
+                primary,
+                secondary,
+                primary_count,
+            } => composite(
+                area,
+                len,
+                primary,
+                secondary,
+                *primary_count,
+                layout_flip,
+                resize_dimensions,
+            )?,
         };
 
+        if gap > 0 {
+            for l in &mut dimensions {
+                add_gap(l, area, gap);
+            }
+        }
+
         dimensions
             .iter_mut()
             .for_each(|l| l.add_padding(container_padding));
 
-        dimensions
+        Ok(dimensions)
+    }
+}
+
+// Unlike padding, a gap is only added to the edges that are shared with another container, so the
+// outermost containers stay flush with the edges of the work area
+fn add_gap(rect: &mut Rect, area: &Rect, gap: i32) {
+    let half_gap = gap / 2;
+
+    if rect.left > area.left {
+        rect.left += half_gap;
+        rect.right -= half_gap;
+    }
+
+    if rect.left + rect.right < area.left + area.right {
+        rect.right -= half_gap;
     }
+
+    if rect.top > area.top {
+        rect.top += half_gap;
+        rect.bottom -= half_gap;
+    }
+
+    if rect.top + rect.bottom < area.top + area.bottom {
+        rect.bottom -= half_gap;
+    }
+}
+
+// Executable path, work area and container count
+type CustomLayoutKey = (String, [i32; 4], usize);
+
+// Layouts are calculated while the window manager is locked, so an executable that takes longer
+// than this is killed rather than being allowed to stop komorebi from handling anything else
+const CUSTOM_LAYOUT_TIMEOUT: Duration = Duration::from_secs(1);
+const CUSTOM_LAYOUT_CACHE_CAPACITY: usize = 64;
+
+static CUSTOM_LAYOUT_CACHE_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Custom layouts are calculated by spawning a process, so the results are kept around for as
+    // long as the work area and the number of containers stay the same, until the cache is cleared
+    static CUSTOM_LAYOUT_CACHE: RefCell<(usize, HashMap<CustomLayoutKey, Vec<Rect>>)> =
+        RefCell::new((0, HashMap::new()));
+}
+
+fn custom(path: &str, area: &Rect, len: usize) -> Result<Vec<Rect>> {
+    if path.is_empty() {
+        return Err(anyhow!(
+            "a path to an executable must be given for the custom layout"
+        ));
+    }
+
+    let key = (
+        path.to_string(),
+        [area.left, area.top, area.right, area.bottom],
+        len,
+    );
+
+    let generation = CUSTOM_LAYOUT_CACHE_GENERATION.load(Ordering::SeqCst);
+    let cached = CUSTOM_LAYOUT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.0 != generation {
+            *cache = (generation, HashMap::new());
+        }
+
+        cache.1.get(&key).cloned()
+    });
+
+    if let Some(layouts) = cached {
+        return Ok(layouts);
+    }
+
+    let mut command = Command::new(path);
+    command
+        .arg(area.left.to_string())
+        .arg(area.top.to_string())
+        .arg(area.right.to_string())
+        .arg(area.bottom.to_string())
+        .arg(len.to_string());
+
+    let output = output_with_timeout(&mut command, path)?;
+    let layouts: Vec<Rect> = serde_json::from_slice(&output)?;
+    if layouts.len() != len {
+        return Err(anyhow!(
+            "custom layout executable {} returned {} layouts for {} containers",
+            path,
+            layouts.len(),
+            len
+        ));
+    }
+
+    CUSTOM_LAYOUT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.1.len() >= CUSTOM_LAYOUT_CACHE_CAPACITY {
+            cache.1.clear();
+        }
+
+        cache.1.insert(key, layouts.clone());
+    });
+
+    Ok(layouts)
+}
+
+fn output_with_timeout(command: &mut Command, path: &str) -> Result<Vec<u8>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // The output is read on another thread so that an executable writing more than the pipe can
+    // hold doesn't block until it gets killed
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("could not read the output of {}", path))?;
+
+    let reader = thread::spawn(move || {
+        let mut output = vec![];
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if started.elapsed() > CUSTOM_LAYOUT_TIMEOUT {
+            child.kill()?;
+            child.wait()?;
+
+            return Err(anyhow!(
+                "custom layout executable {} did not exit within {:?}",
+                path,
+                CUSTOM_LAYOUT_TIMEOUT
+            ));
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    if !status.success() {
+        return Err(anyhow!(
+            "custom layout executable {} exited with {}",
+            path,
+            status
+        ));
+    }
+
+    Ok(reader
+        .join()
+        .map_err(|_| anyhow!("could not read the output of {}", path))??)
+}
+
+// The first primary_count containers are laid out by the primary layout on the left half of the work
+// area and the rest by the secondary layout on the right half. Gaps and padding are left to the
+// caller so that they are only applied once to the combined result
+fn composite(
+    area: &Rect,
+    len: usize,
+    primary: &Layout,
+    secondary: &Layout,
+    primary_count: usize,
+    layout_flip: Option<Flip>,
+    resize_dimensions: &[Option<Rect>],
+) -> Result<Vec<Rect>> {
+    let primary_len = primary_count.min(len);
+    let (primary_resize, secondary_resize) =
+        resize_dimensions.split_at(primary_len.min(resize_dimensions.len()));
+
+    let (primary_len, secondary_len) = match (
+        NonZeroUsize::new(primary_len),
+        NonZeroUsize::new(len - primary_len),
+    ) {
+        (Some(primary_len), Some(secondary_len)) => (primary_len, secondary_len),
+        // When only one of the areas has any containers, it takes the whole work area
+        (Some(primary_len), None) => {
+            return primary.calculate(area, primary_len, None, 0, layout_flip, primary_resize)
+        }
+        (None, Some(secondary_len)) => {
+            return secondary.calculate(area, secondary_len, None, 0, layout_flip, secondary_resize)
+        }
+        (None, None) => return Ok(vec![]),
+    };
+
+    let half_width = area.right / 2;
+    let left = Rect {
+        left: area.left,
+        top: area.top,
+        right: half_width,
+        bottom: area.bottom,
+    };
+
+    let right = Rect {
+        left: area.left + half_width,
+        top: area.top,
+        right: area.right - half_width,
+        bottom: area.bottom,
+    };
+
+    let (primary_area, secondary_area) = match layout_flip {
+        Some(Flip::Horizontal | Flip::HorizontalAndVertical) => (right, left),
+        _ => (left, right),
+    };
+
+    let mut layouts = primary.calculate(
+        &primary_area,
+        primary_len,
+        None,
+        0,
+        layout_flip,
+        primary_resize,
+    )?;
+
+    layouts.extend(secondary.calculate(
+        &secondary_area,
+        secondary_len,
+        None,
+        0,
+        layout_flip,
+        secondary_resize,
+    )?);
+
+    Ok(layouts)
+}
+
+// The main column gets main_ratio percent of the width when there are two columns, and the
+// weighting is kept the same as more columns are added, so a ratio of 50 always gives every column
+// the same width
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn columns(area: &Rect, len: usize, main_ratio: u32) -> Vec<Rect> {
+    let main_ratio = i64::from(main_ratio.clamp(1, 99));
+    let others = len as i64 - 1;
+
+    let main_width =
+        (i64::from(area.right) * main_ratio / (main_ratio + others * (100 - main_ratio))) as i32;
+
+    let other_width = if others > 0 {
+        (area.right - main_width) / others as i32
+    } else {
+        0
+    };
+
+    let mut left = 0;
+    let mut layouts: Vec<Rect> = vec![];
+    for i in 0..len {
+        let right = if i == 0 { main_width } else { other_width };
+
+        layouts.push(Rect {
+            left: area.left + left,
+            top: area.top,
+            right,
+            bottom: area.bottom,
+        });
+
+        left += right;
+    }
+
+    layouts
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn three_column(
+    area: &Rect,
+    len: usize,
+    main_ratio: u32,
+    secondary_ratio: u32,
+    layout_flip: Option<Flip>,
+) -> Vec<Rect> {
+    // The first container always takes the center column, and every container after that
+    // alternates between the left and right columns, stacking from top to bottom
+    let left_count = len / 2;
+    let right_count = len.saturating_sub(1) / 2;
+
+    // Columns without any containers are collapsed so that their space goes to the center
+    let mut total_ratio = main_ratio;
+    if left_count > 0 {
+        total_ratio += secondary_ratio;
+    }
+
+    if right_count > 0 {
+        total_ratio += secondary_ratio;
+    }
+
+    let secondary_width = if total_ratio == 0 {
+        0
+    } else {
+        (i64::from(area.right) * i64::from(secondary_ratio) / i64::from(total_ratio)) as i32
+    };
+
+    let left_width = if left_count > 0 { secondary_width } else { 0 };
+    let right_width = if right_count > 0 { secondary_width } else { 0 };
+    let main_width = area.right - left_width - right_width;
+
+    let (left_x, right_x) = match layout_flip {
+        Some(Flip::Horizontal | Flip::HorizontalAndVertical) => {
+            (area.left + main_width + right_width, area.left)
+        }
+        _ => (area.left, area.left + left_width + main_width),
+    };
+
+    let main_x = match layout_flip {
+        Some(Flip::Horizontal | Flip::HorizontalAndVertical) => area.left + right_width,
+        _ => area.left + left_width,
+    };
+
+    let flip_vertical = matches!(
+        layout_flip,
+        Some(Flip::Vertical | Flip::HorizontalAndVertical)
+    );
+
+    let stacked = |x: i32, width: i32, count: usize, position: usize| {
+        let height = area.bottom / count as i32;
+        let position = if flip_vertical {
+            count - 1 - position
+        } else {
+            position
+        };
+
+        Rect {
+            left: x,
+            top: area.top + (height * position as i32),
+            right: width,
+            bottom: height,
+        }
+    };
+
+    let mut layouts: Vec<Rect> = vec![];
+    for i in 0..len {
+        if i == 0 {
+            layouts.push(Rect {
+                left: main_x,
+                top: area.top,
+                right: main_width,
+                bottom: area.bottom,
+            });
+        } else if i % 2 != 0 {
+            layouts.push(stacked(left_x, left_width, left_count, i / 2));
+        } else {
+            layouts.push(stacked(right_x, right_width, right_count, i / 2 - 1));
+        }
+    }
+
+    layouts
 }
 
 fn calculate_resize_adjustments(resize_dimensions: &[Option<Rect>]) -> Vec<Option<Rect>> {
@@ -386,3 +856,179 @@ fn recursive_fibonacci(
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        left: 0,
+        top: 0,
+        right: 1200,
+        bottom: 600,
+    };
+
+    const fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn calculate(layout: &Layout, len: usize, gap: i32, layout_flip: Option<Flip>) -> Vec<Rect> {
+        layout
+            .calculate(
+                &AREA,
+                NonZeroUsize::new(len).unwrap(),
+                None,
+                gap,
+                layout_flip,
+                &[],
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn three_column_with_gaps() {
+        let layout = Layout::ThreeColumn {
+            main_ratio: 50,
+            secondary_ratio: 25,
+        };
+
+        assert_eq!(
+            calculate(&layout, 4, 0, None),
+            vec![
+                rect(300, 0, 600, 600),
+                rect(0, 0, 300, 300),
+                rect(900, 0, 300, 600),
+                rect(0, 300, 300, 300),
+            ]
+        );
+
+        assert_eq!(
+            calculate(&layout, 4, 20, None),
+            vec![
+                rect(310, 0, 580, 600),
+                rect(0, 0, 290, 290),
+                rect(910, 0, 290, 600),
+                rect(0, 310, 290, 290),
+            ]
+        );
+    }
+
+    #[test]
+    fn three_column_collapses_empty_columns() {
+        let layout = Layout::ThreeColumn {
+            main_ratio: 50,
+            secondary_ratio: 25,
+        };
+
+        assert_eq!(calculate(&layout, 1, 20, None), vec![AREA]);
+        assert_eq!(
+            calculate(&layout, 2, 0, None),
+            vec![rect(400, 0, 800, 600), rect(0, 0, 400, 600)]
+        );
+        assert_eq!(
+            calculate(&layout, 2, 0, Option::from(Flip::Horizontal)),
+            vec![rect(0, 0, 800, 600), rect(800, 0, 400, 600)]
+        );
+    }
+
+    #[test]
+    fn columns_with_gaps() {
+        let layout = Layout::Columns { main_ratio: 50 };
+
+        assert_eq!(
+            calculate(&layout, 3, 0, None),
+            vec![
+                rect(0, 0, 400, 600),
+                rect(400, 0, 400, 600),
+                rect(800, 0, 400, 600),
+            ]
+        );
+
+        assert_eq!(
+            calculate(&layout, 3, 20, None),
+            vec![
+                rect(0, 0, 390, 600),
+                rect(410, 0, 380, 600),
+                rect(810, 0, 390, 600),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_with_main_ratio() {
+        let layout = Layout::Columns { main_ratio: 60 };
+
+        assert_eq!(
+            calculate(&layout, 2, 0, None),
+            vec![rect(0, 0, 720, 600), rect(720, 0, 480, 600)]
+        );
+        assert_eq!(calculate(&layout, 1, 20, None), vec![AREA]);
+    }
+
+    #[test]
+    fn composite_with_gaps() {
+        let layout = Layout::Composite {
+            primary: Box::new(Layout::Columns { main_ratio: 50 }),
+            secondary: Box::new(Layout::Rows),
+            primary_count: 2,
+        };
+
+        assert_eq!(
+            calculate(&layout, 4, 0, None),
+            vec![
+                rect(0, 0, 300, 600),
+                rect(300, 0, 300, 600),
+                rect(600, 0, 600, 300),
+                rect(600, 300, 600, 300),
+            ]
+        );
+
+        assert_eq!(
+            calculate(&layout, 4, 20, None),
+            vec![
+                rect(0, 0, 290, 600),
+                rect(310, 0, 280, 600),
+                rect(610, 0, 590, 290),
+                rect(610, 310, 590, 290),
+            ]
+        );
+    }
+
+    #[test]
+    fn composite_with_one_half_empty() {
+        let layout = Layout::Composite {
+            primary: Box::new(Layout::Columns { main_ratio: 50 }),
+            secondary: Box::new(Layout::Rows),
+            primary_count: 2,
+        };
+
+        assert_eq!(
+            calculate(&layout, 2, 0, None),
+            vec![rect(0, 0, 600, 600), rect(600, 0, 600, 600)]
+        );
+        assert_eq!(calculate(&layout, 1, 20, None), vec![AREA]);
+    }
+
+    #[test]
+    fn composite_flipped_horizontally() {
+        let layout = Layout::Composite {
+            primary: Box::new(Layout::Rows),
+            secondary: Box::new(Layout::Rows),
+            primary_count: 1,
+        };
+
+        assert_eq!(
+            calculate(&layout, 3, 0, Option::from(Flip::Horizontal)),
+            vec![
+                rect(600, 0, 600, 600),
+                rect(0, 0, 600, 300),
+                rect(0, 300, 600, 300),
+            ]
+        );
+    }
+}
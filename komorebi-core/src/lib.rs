@@ -4,6 +4,7 @@
 use std::str::FromStr;
 
 use clap::ArgEnum;
+use color_eyre::eyre::anyhow;
 use color_eyre::Result;
 use serde::Deserialize;
 use serde::Serialize;
@@ -14,6 +15,7 @@ pub use cycle_direction::CycleDirection;
 pub use layout::Flip;
 pub use layout::Layout;
 pub use operation_direction::OperationDirection;
+pub use rect::scale_to_dpi;
 pub use rect::Rect;
 
 pub mod cycle_direction;
@@ -25,48 +27,143 @@ pub mod rect;
 pub enum SocketMessage {
     // Window / Container Commands
     FocusWindow(OperationDirection),
+    FocusWindowUnderCursor,
+    FocusFloatingWindow(CycleDirection),
+    FocusTiledWindow,
+    CycleContainerAcrossWorkspaces(CycleDirection),
     MoveWindow(OperationDirection),
     StackWindow(OperationDirection),
     ResizeWindow(OperationDirection, Sizing),
+    ResizeWindowAbsolute(Option<i32>, Option<i32>),
+    SetMinimumContainerSize(u32, u32),
+    LockContainerAspectRatio(u32, u32),
+    UnlockContainerAspectRatio,
     UnstackWindow,
     CycleStack(CycleDirection),
+    MoveWindowInContainerToPosition(usize),
+    SplitContainer(usize),
+    MergeContainersInDirection(OperationDirection),
+    SendToNamedScratchpad(String),
+    ToggleNamedScratchpad(String),
+    ToggleWorkspaceScratchpad(usize, usize),
     MoveContainerToMonitorNumber(usize),
     MoveContainerToWorkspaceNumber(usize),
+    MoveContainerToNextNonEmptyWorkspace(CycleDirection),
     Promote,
+    ReorderContainersByExe,
+    ReorderContainersByTitle,
+    RotateContainers(CycleDirection),
+    ShuffleContainers,
     ToggleFloat,
+    CenterFloatingWindow,
+    CenterFloatingWindowOnMonitor(usize),
+    MoveFloatingWindowToTile(usize),
+    TileAllFloatingWindows,
+    FloatAllContainerWindows,
+    MemorizeWindowPosition,
+    RestoreWindowPosition,
+    PinFocusedWindowToWorkspace,
     ToggleMonocle,
+    CycleMonocle(CycleDirection),
     ToggleMaximize,
+    ToggleSoftMaximize,
+    ToggleWindowBorder,
+    ToggleFocusBorder,
+    SetFocusBorderColor(u8, u8, u8),
+    SetUnfocusBorderColor(u8, u8, u8),
+    ToggleTitleBars,
+    ToggleWindowDecoration,
     // Current Workspace Commands
     ManageFocusedWindow,
     UnmanageFocusedWindow,
+    UnmanageWindow(isize),
     AdjustContainerPadding(Sizing, i32),
     AdjustWorkspacePadding(Sizing, i32),
     ChangeLayout(Layout),
     FlipLayout(Flip),
+    UndoLayoutChange,
+    UndoFlipLayout,
+    SetThreeColumnRatio(u32, u32),
+    SetColumnsMainRatio(f32),
     // Monitor and Workspace Commands
     EnsureWorkspaces(usize, usize),
+    EnsureWorkspacesForAllMonitors(usize),
+    SetMaxWorkspaces(usize, usize),
+    SetMonitorWorkAreaOffset(usize, Rect),
+    MapMonitorToIndex(String, usize),
     NewWorkspace,
+    NewNamedWorkspace(Option<String>),
     ToggleTiling,
+    ToggleGapless,
     Stop,
+    StopWithSaveState,
+    RestoreState,
     TogglePause,
+    TogglePauseMonitor(usize),
     Retile,
     FocusMonitorNumber(usize),
+    FocusMonitorInDirection(OperationDirection),
+    FocusCycleMonitor(CycleDirection),
+    FocusPrimaryMonitor,
     FocusWorkspaceNumber(usize),
+    #[serde(alias = "SetFocusedWorkspaceOnMonitor")]
+    SetFocusedMonitorWorkspace(usize, usize),
     ContainerPadding(usize, usize, i32),
     WorkspacePadding(usize, usize, i32),
+    SetWorkspacePaddingFromEdge(usize, usize, Rect),
+    SetWorkspaceGap(usize, usize, i32),
+    SetContainerPaddingForMonitor(usize, i32),
+    SetWorkspacePaddingForMonitor(usize, i32),
+    SetGlobalContainerPadding(i32),
+    SetGlobalWorkspacePadding(i32),
     WorkspaceTiling(usize, usize, bool),
     WorkspaceName(usize, usize, String),
     WorkspaceLayout(usize, usize, Layout),
+    SetWorkspaceDefaultLayout(usize, Layout),
+    SetGlobalDefaultLayout(Layout),
+    WorkspaceLayoutFlip(usize, usize, Flip),
+    WorkspaceMouseFollowsFocus(usize, usize, bool),
     // Configuration
     ReloadConfiguration,
     WatchConfiguration(bool),
-    WorkspaceRule(ApplicationIdentifier, String, usize, usize),
+    WorkspaceRule(ApplicationIdentifier, String, MonitorWorkspaceMapping),
+    WorkspaceRuleWithCondition(
+        ApplicationIdentifier,
+        String,
+        MonitorWorkspaceMapping,
+        WorkspaceRuleCondition,
+    ),
     FloatRule(ApplicationIdentifier, String),
     ManageRule(ApplicationIdentifier, String),
     IdentifyTrayApplication(ApplicationIdentifier, String),
+    IdentifyIgnoreTitle(String),
+    SetApplicationContainerPadding(ApplicationIdentifier, String, i32),
+    SuppressEventForExe(WindowManagerEventType, String),
+    SetEventDebounce(u64),
     State,
+    Ping,
+    GetContainerForWindow(isize),
+    IsManaged(isize),
+    GetProcessIdForWindow(isize),
+    GetFocusedWindowGeometry,
+    GetFocusedWorkspaceIndex,
+    MonitorInfo,
+    WorkspaceWindowCounts,
+    DumpWindowTree,
+    Version,
+    AddSubscriber(String),
     FocusFollowsMouse(bool),
     ToggleFocusFollowsMouse,
+    ToggleAutoFullscreenPause(bool),
+    ToggleMonocleIncludeFloating(bool),
+    ToggleAutoManage(bool),
+    ToggleAutoStackByExe(bool),
+    EnableVirtualDesktopIntegration(bool),
+    ToggleDynamicWorkspaceNaming(bool),
+    ToggleDpiAwarePadding(bool),
+    TraceWindow(isize),
+    StopTracing,
+    SetWindowOpacity(isize, u8),
 }
 
 impl SocketMessage {
@@ -77,6 +174,23 @@ impl SocketMessage {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
         Ok(serde_json::from_slice(bytes)?)
     }
+
+    #[must_use]
+    pub const fn is_query(&self) -> bool {
+        matches!(
+            self,
+            Self::State
+                | Self::GetContainerForWindow(_)
+                | Self::IsManaged(_)
+                | Self::GetProcessIdForWindow(_)
+                | Self::GetFocusedWindowGeometry
+                | Self::GetFocusedWorkspaceIndex
+                | Self::MonitorInfo
+                | Self::WorkspaceWindowCounts
+                | Self::DumpWindowTree
+                | Self::Version
+        )
+    }
 }
 
 impl FromStr for SocketMessage {
@@ -87,12 +201,134 @@ impl FromStr for SocketMessage {
     }
 }
 
+// Written back on the same connection after each message has been processed, with the requested
+// data in response to queries, so that komorebic can exit with an error when a command fails
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProcessCommandResponse {
+    pub success: bool,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl ProcessCommandResponse {
+    #[must_use]
+    pub const fn ok() -> Self {
+        Self {
+            success: true,
+            data: None,
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn data(data: serde_json::Value) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn error(error: String) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+
+    #[must_use]
+    pub fn pong() -> Self {
+        Self::data(serde_json::json!({
+            "pong": true,
+            "pid": std::process::id(),
+        }))
+    }
+
+    pub fn into_data(self) -> Result<Option<serde_json::Value>> {
+        if self.success {
+            Ok(self.data)
+        } else {
+            Err(anyhow!(self.error.unwrap_or_else(|| String::from(
+                "komorebi could not process the command"
+            ))))
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Display, EnumString, ArgEnum)]
 #[strum(serialize_all = "snake_case")]
 pub enum ApplicationIdentifier {
     Exe,
     Class,
     Title,
+    Path,
+}
+
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ArgEnum, PartialEq, Eq, Hash,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum WindowManagerEventType {
+    Destroy,
+    FocusChange,
+    Hide,
+    Minimize,
+    Show,
+    MoveResizeEnd,
+    MouseCapture,
+    TitleChange,
+}
+
+// Monitor indices in a mapping follow any preferences set with MapMonitorToIndex, so that rules
+// keep pointing at the same physical monitor when Windows reorders them
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MonitorWorkspaceMapping {
+    pub monitor_idx: usize,
+    pub workspace_idx: usize,
+}
+
+impl MonitorWorkspaceMapping {
+    #[must_use]
+    pub const fn new(monitor_idx: usize, workspace_idx: usize) -> Self {
+        Self {
+            monitor_idx,
+            workspace_idx,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WorkspaceRuleCondition {
+    Always,
+    MonitorCount(usize),
+}
+
+impl WorkspaceRuleCondition {
+    #[must_use]
+    pub const fn is_met(self, monitor_count: usize) -> bool {
+        match self {
+            Self::Always => true,
+            Self::MonitorCount(count) => count == monitor_count,
+        }
+    }
+}
+
+// Conditions are passed to komorebic as "always" or "monitor_count:<count>"
+impl FromStr for WorkspaceRuleCondition {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "always" => Ok(Self::Always),
+            Some(("monitor_count", count)) => Ok(Self::MonitorCount(count.parse()?)),
+            _ => Err(anyhow!(
+                "{} is not a valid condition, expected always or monitor_count:<count>",
+                s
+            )),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, ArgEnum)]
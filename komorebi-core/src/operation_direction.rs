@@ -27,7 +27,8 @@ impl OperationDirection {
         }
     }
 
-    fn flip_direction(direction: Self, layout_flip: Option<Flip>) -> Self {
+    #[must_use]
+    pub fn flip_direction(direction: Self, layout_flip: Option<Flip>) -> Self {
         layout_flip.map_or(direction, |flip| match direction {
             Self::Left => match flip {
                 Flip::Horizontal | Flip::HorizontalAndVertical => Self::Right,
@@ -51,38 +52,70 @@ impl OperationDirection {
     #[must_use]
     pub fn is_valid(
         self,
-        layout: Layout,
+        layout: &Layout,
         layout_flip: Option<Flip>,
         idx: usize,
         len: usize,
     ) -> bool {
-        match Self::flip_direction(self, layout_flip) {
+        let direction = Self::flip_direction(self, layout_flip);
+        match direction {
             OperationDirection::Up => match layout {
                 Layout::BSP => len > 2 && idx != 0 && idx != 1,
-                Layout::Columns => false,
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => false,
                 Layout::Rows => idx != 0,
+                Layout::ThreeColumn { .. } => idx > 2,
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.is_valid_for_composite(primary, secondary, *primary_count, idx, len),
             },
             OperationDirection::Down => match layout {
                 Layout::BSP => len > 2 && idx != len - 1 && idx % 2 != 0,
-                Layout::Columns => false,
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => false,
                 Layout::Rows => idx != len - 1,
+                Layout::ThreeColumn { .. } => idx != 0 && idx + 2 < len,
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.is_valid_for_composite(primary, secondary, *primary_count, idx, len),
             },
             OperationDirection::Left => match layout {
                 Layout::BSP => len > 1 && idx != 0,
-                Layout::Columns => idx != 0,
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => idx != 0,
                 Layout::Rows => false,
+                Layout::ThreeColumn { .. } => (idx == 0 && len > 1) || (idx != 0 && idx % 2 == 0),
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.is_valid_for_composite(primary, secondary, *primary_count, idx, len),
             },
             OperationDirection::Right => match layout {
                 Layout::BSP => len > 1 && idx % 2 == 0 && idx != len - 1,
-                Layout::Columns => idx != len - 1,
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => idx != len - 1,
                 Layout::Rows => false,
+                Layout::ThreeColumn { .. } => (idx == 0 && len > 2) || idx % 2 != 0,
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.is_valid_for_composite(primary, secondary, *primary_count, idx, len),
             },
         }
     }
 
     #[must_use]
-    pub fn new_idx(self, layout: Layout, layout_flip: Option<Flip>, idx: usize) -> usize {
-        match Self::flip_direction(self, layout_flip) {
+    pub fn new_idx(
+        self,
+        layout: &Layout,
+        layout_flip: Option<Flip>,
+        idx: usize,
+        len: usize,
+    ) -> usize {
+        let direction = Self::flip_direction(self, layout_flip);
+        match direction {
             Self::Up => match layout {
                 Layout::BSP => {
                     if idx % 2 == 0 {
@@ -91,12 +124,24 @@ impl OperationDirection {
                         idx - 2
                     }
                 }
-                Layout::Columns => unreachable!(),
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => unreachable!(),
                 Layout::Rows => idx - 1,
+                Layout::ThreeColumn { .. } => idx - 2,
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.new_idx_for_composite(primary, secondary, *primary_count, idx, len),
             },
             Self::Down => match layout {
                 Layout::BSP | Layout::Rows => idx + 1,
-                Layout::Columns => unreachable!(),
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => unreachable!(),
+                Layout::ThreeColumn { .. } => idx + 2,
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.new_idx_for_composite(primary, secondary, *primary_count, idx, len),
             },
             Self::Left => match layout {
                 Layout::BSP => {
@@ -106,13 +151,191 @@ impl OperationDirection {
                         idx - 1
                     }
                 }
-                Layout::Columns => idx - 1,
+                Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => idx - 1,
                 Layout::Rows => unreachable!(),
+                Layout::ThreeColumn { .. } => {
+                    if idx == 0 {
+                        1
+                    } else {
+                        0
+                    }
+                }
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.new_idx_for_composite(primary, secondary, *primary_count, idx, len),
             },
             Self::Right => match layout {
-                Layout::BSP | Layout::Columns => idx + 1,
+                Layout::BSP | Layout::Columns { .. } | Layout::Deck | Layout::Custom(_) => idx + 1,
                 Layout::Rows => unreachable!(),
+                Layout::ThreeColumn { .. } => {
+                    if idx == 0 {
+                        2
+                    } else {
+                        0
+                    }
+                }
+                Layout::Composite {
+                    primary,
+                    secondary,
+                    primary_count,
+                } => direction.new_idx_for_composite(primary, secondary, *primary_count, idx, len),
             },
         }
     }
+
+    // The first primary_count containers of a composite layout are laid out in the left half by the
+    // primary layout, and the rest in the right half by the secondary layout. Directions are
+    // already flipped by the time we get here, so each half is handled as if it was unflipped.
+    // Within a half the direction follows that half's layout, and going past its edge towards the
+    // other half lands on the last container of the primary half or the first of the secondary
+    fn is_valid_for_composite(
+        self,
+        primary: &Layout,
+        secondary: &Layout,
+        primary_count: usize,
+        idx: usize,
+        len: usize,
+    ) -> bool {
+        let primary_len = primary_count.min(len);
+
+        if idx < primary_len {
+            self.is_valid(primary, None, idx, primary_len)
+                || (matches!(self, Self::Right) && len > primary_len)
+        } else {
+            self.is_valid(secondary, None, idx - primary_len, len - primary_len)
+                || (matches!(self, Self::Left) && primary_len > 0)
+        }
+    }
+
+    fn new_idx_for_composite(
+        self,
+        primary: &Layout,
+        secondary: &Layout,
+        primary_count: usize,
+        idx: usize,
+        len: usize,
+    ) -> usize {
+        let primary_len = primary_count.min(len);
+
+        if idx < primary_len {
+            if self.is_valid(primary, None, idx, primary_len) {
+                self.new_idx(primary, None, idx, primary_len)
+            } else {
+                primary_len
+            }
+        } else if self.is_valid(secondary, None, idx - primary_len, len - primary_len) {
+            primary_len + self.new_idx(secondary, None, idx - primary_len, len - primary_len)
+        } else {
+            primary_len - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OperationDirection::Down;
+    use OperationDirection::Left;
+    use OperationDirection::Right;
+    use OperationDirection::Up;
+
+    // Returns the index that moving in the direction would land on, if the move is valid
+    fn target(
+        direction: OperationDirection,
+        layout: &Layout,
+        layout_flip: Option<Flip>,
+        idx: usize,
+        len: usize,
+    ) -> Option<usize> {
+        if direction.is_valid(layout, layout_flip, idx, len) {
+            Option::from(direction.new_idx(layout, layout_flip, idx, len))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn bsp() {
+        let layout = Layout::BSP;
+
+        assert_eq!(target(Right, &layout, None, 0, 3), Some(1));
+        assert_eq!(target(Down, &layout, None, 1, 3), Some(2));
+        assert_eq!(target(Up, &layout, None, 2, 3), Some(1));
+        assert_eq!(target(Left, &layout, None, 1, 3), Some(0));
+        assert_eq!(target(Left, &layout, None, 0, 3), None);
+        assert_eq!(target(Down, &layout, None, 2, 3), None);
+    }
+
+    #[test]
+    fn columns_and_rows() {
+        let columns = Layout::Columns { main_ratio: 50 };
+
+        assert_eq!(target(Right, &columns, None, 0, 2), Some(1));
+        assert_eq!(target(Right, &columns, None, 1, 2), None);
+        assert_eq!(target(Down, &columns, None, 0, 2), None);
+
+        assert_eq!(target(Down, &Layout::Rows, None, 0, 2), Some(1));
+        assert_eq!(target(Up, &Layout::Rows, None, 0, 2), None);
+        assert_eq!(target(Left, &Layout::Rows, None, 1, 2), None);
+    }
+
+    #[test]
+    fn flipped_directions() {
+        let columns = Layout::Columns { main_ratio: 50 };
+        let flip = Option::from(Flip::Horizontal);
+
+        assert_eq!(target(Left, &columns, flip, 0, 2), Some(1));
+        assert_eq!(target(Right, &columns, flip, 0, 2), None);
+
+        let flip = Option::from(Flip::Vertical);
+
+        assert_eq!(target(Up, &Layout::Rows, flip, 0, 2), Some(1));
+    }
+
+    #[test]
+    fn three_column() {
+        let layout = Layout::ThreeColumn {
+            main_ratio: 50,
+            secondary_ratio: 25,
+        };
+
+        assert_eq!(target(Left, &layout, None, 0, 4), Some(1));
+        assert_eq!(target(Right, &layout, None, 0, 4), Some(2));
+        assert_eq!(target(Right, &layout, None, 1, 4), Some(0));
+        assert_eq!(target(Left, &layout, None, 2, 4), Some(0));
+        assert_eq!(target(Down, &layout, None, 1, 4), Some(3));
+        assert_eq!(target(Up, &layout, None, 3, 4), Some(1));
+        assert_eq!(target(Right, &layout, None, 0, 2), None);
+        assert_eq!(target(Down, &layout, None, 2, 4), None);
+    }
+
+    #[test]
+    fn composite() {
+        let layout = Layout::Composite {
+            primary: Box::new(Layout::Columns { main_ratio: 50 }),
+            secondary: Box::new(Layout::Rows),
+            primary_count: 2,
+        };
+
+        // Within the primary half
+        assert_eq!(target(Right, &layout, None, 0, 4), Some(1));
+        assert_eq!(target(Down, &layout, None, 0, 4), None);
+
+        // Within the secondary half
+        assert_eq!(target(Down, &layout, None, 2, 4), Some(3));
+        assert_eq!(target(Up, &layout, None, 3, 4), Some(2));
+        assert_eq!(target(Right, &layout, None, 3, 4), None);
+
+        // Across the halves
+        assert_eq!(target(Right, &layout, None, 1, 4), Some(2));
+        assert_eq!(target(Left, &layout, None, 3, 4), Some(1));
+        assert_eq!(target(Right, &layout, None, 1, 2), None);
+
+        let flip = Option::from(Flip::Horizontal);
+
+        assert_eq!(target(Left, &layout, flip, 1, 4), Some(2));
+        assert_eq!(target(Right, &layout, flip, 2, 4), Some(1));
+    }
 }
@@ -1,8 +1,9 @@
+use serde::Deserialize;
 use serde::Serialize;
 
 use bindings::Windows::Win32::Foundation::RECT;
 
-#[derive(Debug, Clone, Copy, Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Rect {
     pub left: i32,
     pub top: i32,
@@ -42,6 +43,45 @@ impl Rect {
         }
     }
 
+    #[must_use]
+    pub fn scale(&self, scale_factor: f64) -> Self {
+        Self {
+            left: scale_to_dpi(self.left, scale_factor),
+            top: scale_to_dpi(self.top, scale_factor),
+            right: scale_to_dpi(self.right, scale_factor),
+            bottom: scale_to_dpi(self.bottom, scale_factor),
+        }
+    }
+
+    // Each field of the padding is the distance to leave from the corresponding edge
+    pub fn add_edge_padding(&mut self, padding: &Self) {
+        self.left += padding.left;
+        self.top += padding.top;
+        self.right -= padding.left + padding.right;
+        self.bottom -= padding.top + padding.bottom;
+    }
+
+    // Shrinks whichever dimension is too large for the ratio, keeping the rect centered where it was
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn constrain_to_aspect_ratio(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let (ratio_width, ratio_height) = (i64::from(width), i64::from(height));
+        let (current_width, current_height) = (i64::from(self.right), i64::from(self.bottom));
+
+        if current_width * ratio_height > current_height * ratio_width {
+            let constrained = (current_height * ratio_width / ratio_height) as i32;
+            self.left += (self.right - constrained) / 2;
+            self.right = constrained;
+        } else {
+            let constrained = (current_width * ratio_height / ratio_width) as i32;
+            self.top += (self.bottom - constrained) / 2;
+            self.bottom = constrained;
+        }
+    }
+
     #[must_use]
     pub const fn contains_point(&self, point: (i32, i32)) -> bool {
         point.0 >= self.left
@@ -50,3 +90,11 @@ impl Rect {
             && point.1 <= self.top + self.bottom
     }
 }
+
+// Padding and gaps are given in pixels at 100% scaling, and multiplied by the scale factor of the
+// monitor they are applied on
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn scale_to_dpi(value: i32, scale_factor: f64) -> i32 {
+    (f64::from(value) * scale_factor).round() as i32
+}
@@ -1,18 +1,24 @@
 use std::collections::VecDeque;
 
+use color_eyre::eyre::anyhow;
+use color_eyre::Result;
+use getset::CopyGetters;
 use getset::Getters;
+use getset::Setters;
 use nanoid::nanoid;
 use serde::Serialize;
 
 use crate::ring::Ring;
 use crate::window::Window;
 
-#[derive(Debug, Clone, Serialize, Getters)]
+#[derive(Debug, Clone, Serialize, Getters, CopyGetters, Setters)]
 pub struct Container {
     #[serde(skip_serializing)]
     #[getset(get = "pub")]
     id: String,
     windows: Ring<Window>,
+    #[getset(get_copy = "pub", set = "pub")]
+    aspect_ratio: Option<(u32, u32)>,
 }
 
 impl_ring_elements!(Container, Window);
@@ -22,6 +28,7 @@ impl Default for Container {
         Self {
             id: nanoid!(),
             windows: Ring::default(),
+            aspect_ratio: None,
         }
     }
 }
@@ -80,6 +87,58 @@ impl Container {
         window
     }
 
+    pub fn move_window_to_position(&mut self, from: usize, to: usize) -> Result<()> {
+        if to >= self.windows().len() {
+            return Err(anyhow!("this is not a valid position in the container"));
+        }
+
+        let window = self
+            .remove_window_by_idx(from)
+            .ok_or_else(|| anyhow!("there is no window"))?;
+
+        self.windows_mut().insert(to, window);
+
+        // Keep focus on the same window, wherever it has ended up
+        let focused_idx = self.focused_window_idx();
+        if focused_idx == from {
+            self.focus_window(to);
+        } else if from < focused_idx && focused_idx <= to {
+            self.focus_window(focused_idx - 1);
+        } else if to <= focused_idx && focused_idx < from {
+            self.focus_window(focused_idx + 1);
+        }
+
+        Ok(())
+    }
+
+    // Windows before `at` stay in this container and windows from `at` onwards move to a new
+    // container; each side keeps focus on the focused window if it has it, or the nearest one
+    pub fn split(mut self, at: usize) -> (Self, Self) {
+        let focused_idx = self.focused_window_idx();
+
+        let mut other = Self::default();
+        *other.windows_mut() = self.windows_mut().split_off(at);
+
+        if focused_idx < at {
+            other.focus_window(0);
+        } else {
+            self.focus_window(at - 1);
+            other.focus_window(focused_idx - at);
+        }
+
+        (self, other)
+    }
+
+    // The inverse of split; the windows of the other container are appended to the end of this
+    // container, and focus stays on the window that was focused in this container
+    pub fn merge(mut self, mut other: Self) -> Self {
+        let focused_idx = self.focused_window_idx();
+        self.windows_mut().append(other.windows_mut());
+        self.focus_window(focused_idx);
+
+        self
+    }
+
     pub fn add_window(&mut self, window: Window) {
         self.windows_mut().push_back(window);
         self.focus_window(self.windows().len() - 1);
@@ -91,3 +150,93 @@ impl Container {
         self.windows.focus(idx);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(hwnds: &[isize], focused_idx: usize) -> Container {
+        let mut container = Container::default();
+        for hwnd in hwnds {
+            container.add_window(Window { hwnd: *hwnd });
+        }
+
+        container.focus_window(focused_idx);
+        container
+    }
+
+    fn hwnds(container: &Container) -> Vec<isize> {
+        container
+            .windows()
+            .iter()
+            .map(|window| window.hwnd)
+            .collect()
+    }
+
+    #[test]
+    fn split_with_focus_before_index() {
+        let (left, right) = container(&[1, 2, 3, 4], 1).split(2);
+
+        assert_eq!(hwnds(&left), vec![1, 2]);
+        assert_eq!(hwnds(&right), vec![3, 4]);
+        assert_eq!(left.focused_window_idx(), 1);
+        assert_eq!(right.focused_window_idx(), 0);
+    }
+
+    #[test]
+    fn split_with_focus_after_index() {
+        let (left, right) = container(&[1, 2, 3, 4], 3).split(1);
+
+        assert_eq!(hwnds(&left), vec![1]);
+        assert_eq!(hwnds(&right), vec![2, 3, 4]);
+        assert_eq!(left.focused_window_idx(), 0);
+        assert_eq!(right.focused_window_idx(), 2);
+    }
+
+    #[test]
+    fn merge_appends_and_keeps_focus() {
+        let merged = container(&[1, 2], 0).merge(container(&[3, 4], 1));
+
+        assert_eq!(hwnds(&merged), vec![1, 2, 3, 4]);
+        assert_eq!(merged.focused_window_idx(), 0);
+    }
+
+    #[test]
+    fn merge_is_the_inverse_of_split() {
+        let (left, right) = container(&[1, 2, 3], 1).split(1);
+        let merged = left.merge(right);
+
+        assert_eq!(hwnds(&merged), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn move_focused_window_forwards() {
+        let mut container = container(&[1, 2, 3, 4], 0);
+        container.move_window_to_position(0, 2).unwrap();
+
+        assert_eq!(hwnds(&container), vec![2, 3, 1, 4]);
+        assert_eq!(container.focused_window_idx(), 2);
+    }
+
+    #[test]
+    fn move_window_keeps_focus_on_the_same_window() {
+        let mut container = container(&[1, 2, 3, 4], 2);
+        container.move_window_to_position(3, 0).unwrap();
+
+        assert_eq!(hwnds(&container), vec![4, 1, 2, 3]);
+        assert_eq!(container.focused_window_idx(), 3);
+
+        container.move_window_to_position(0, 3).unwrap();
+
+        assert_eq!(hwnds(&container), vec![1, 2, 3, 4]);
+        assert_eq!(container.focused_window_idx(), 2);
+    }
+
+    #[test]
+    fn move_window_to_invalid_position() {
+        let mut container = container(&[1, 2], 0);
+
+        assert!(container.move_window_to_position(0, 2).is_err());
+        assert_eq!(hwnds(&container), vec![1, 2]);
+    }
+}
@@ -2,12 +2,15 @@
 #![allow(clippy::missing_errors_doc)]
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Arc;
 #[cfg(feature = "deadlock_detection")]
 use std::thread;
 #[cfg(feature = "deadlock_detection")]
 use std::time::Duration;
+use std::time::Instant;
 
 use color_eyre::eyre::anyhow;
 use color_eyre::Result;
@@ -17,12 +20,19 @@ use lazy_static::lazy_static;
 #[cfg(feature = "deadlock_detection")]
 use parking_lot::deadlock;
 use parking_lot::Mutex;
+use regex::Regex;
 use sysinfo::SystemExt;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::EnvFilter;
 use which::which;
 
+use komorebi_core::Layout;
+use komorebi_core::MonitorWorkspaceMapping;
+use komorebi_core::Rect;
+use komorebi_core::WindowManagerEventType;
+use komorebi_core::WorkspaceRuleCondition;
+
 use crate::process_command::listen_for_commands;
 use crate::process_event::listen_for_events;
 use crate::window_manager::WindowManager;
@@ -47,6 +57,10 @@ mod winevent;
 mod winevent_listener;
 mod workspace;
 
+type WorkspaceRule = (MonitorWorkspaceMapping, WorkspaceRuleCondition);
+type CachedWindowLocation = (usize, usize, usize);
+type EventDebounceKey = (WindowManagerEventType, isize);
+
 lazy_static! {
     static ref HIDDEN_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
     static ref LAYERED_EXE_WHITELIST: Arc<Mutex<Vec<String>>> =
@@ -65,13 +79,51 @@ lazy_static! {
         "firefox.exe".to_string(),
         "idea64.exe".to_string(),
     ]));
-    static ref WORKSPACE_RULES: Arc<Mutex<HashMap<String, (usize, usize)>>> =
+    static ref WORKSPACE_RULES: Arc<Mutex<HashMap<String, WorkspaceRule>>> =
         Arc::new(Mutex::new(HashMap::new()));
     static ref MANAGE_IDENTIFIERS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
     static ref FLOAT_IDENTIFIERS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    static ref IGNORED_TITLES: Arc<Mutex<Vec<Regex>>> = Arc::new(Mutex::new(vec![]));
+    static ref MONITOR_INDEX_PREFERENCES: Arc<Mutex<HashMap<String, usize>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref PINNED_TO_WORKSPACE: Arc<Mutex<HashMap<isize, (usize, usize)>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref WINDOW_LOCATION_CACHE: Arc<Mutex<HashMap<isize, CachedWindowLocation>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref CONTAINER_PADDING_RULES: Arc<Mutex<HashMap<String, i32>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref SUPPRESSED_EVENTS: Arc<Mutex<HashMap<String, HashSet<WindowManagerEventType>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref WINEVENT_DEBOUNCE_MS: Arc<Mutex<u64>> = Arc::new(Mutex::new(0));
+    static ref LAST_EVENT_TIMES: Arc<Mutex<HashMap<EventDebounceKey, Instant>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref DEBOUNCED_EVENTS: Arc<Mutex<HashMap<EventDebounceKey, WindowManagerEvent>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref MINIMUM_CONTAINER_SIZE: Arc<Mutex<(u32, u32)>> = Arc::new(Mutex::new((0, 0)));
+    static ref AUTO_MANAGE: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    static ref AUTO_STACK_BY_EXE: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    static ref DPI_AWARE_PADDING: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    static ref TRACED_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
+    static ref SUBSCRIPTION_SOCKETS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    static ref KNOWN_TITLES: Arc<Mutex<HashMap<isize, String>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref TITLE_BARLESS_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
+    static ref DECORATIONLESS_HWNDS: Arc<Mutex<Vec<isize>>> = Arc::new(Mutex::new(vec![]));
+    static ref MEMORIZED_POSITIONS: Arc<Mutex<HashMap<isize, Rect>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref BORDER_RECT_CACHE: Arc<Mutex<HashMap<isize, Rect>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref FOCUS_BORDER_COLOR: Arc<Mutex<Option<(u8, u8, u8)>>> = Arc::new(Mutex::new(None));
+    static ref UNFOCUS_BORDER_COLOR: Arc<Mutex<Option<(u8, u8, u8)>>> = Arc::new(Mutex::new(None));
+    static ref DEFAULT_CONTAINER_PADDING: Arc<Mutex<Option<i32>>> =
+        Arc::new(Mutex::new(Option::from(10)));
+    static ref DEFAULT_WORKSPACE_PADDING: Arc<Mutex<Option<i32>>> =
+        Arc::new(Mutex::new(Option::from(10)));
+    static ref DEFAULT_LAYOUT: Arc<Mutex<Layout>> = Arc::new(Mutex::new(Layout::BSP));
+    static ref COLUMNS_MAIN_RATIO: Arc<Mutex<f32>> = Arc::new(Mutex::new(0.5));
 }
 
-fn setup() -> Result<(WorkerGuard, WorkerGuard)> {
+fn setup() -> Result<(WorkerGuard, WorkerGuard, Option<WorkerGuard>)> {
     if std::env::var("RUST_LIB_BACKTRACE").is_err() {
         std::env::set_var("RUST_LIB_BACKTRACE", "1");
     }
@@ -88,6 +140,24 @@ fn setup() -> Result<(WorkerGuard, WorkerGuard)> {
     let (non_blocking, guard) = tracing_appender::non_blocking(appender);
     let (color_non_blocking, color_guard) = tracing_appender::non_blocking(color_appender);
 
+    // Structured logs are opt-in, as they are only useful for scripts using `komorebic log --json`
+    let (json_layer, json_guard) = if std::env::var("KOMOREBI_JSON_LOG").is_ok() {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
+        let json_appender = tracing_appender::rolling::never(home, "komorebi.json.log");
+        let (json_non_blocking, json_guard) = tracing_appender::non_blocking(json_appender);
+
+        (
+            Option::from(
+                tracing_subscriber::fmt::Layer::default()
+                    .json()
+                    .with_writer(json_non_blocking),
+            ),
+            Option::from(json_guard),
+        )
+    } else {
+        (None, None)
+    };
+
     tracing::subscriber::set_global_default(
         tracing_subscriber::fmt::Subscriber::builder()
             .with_env_filter(EnvFilter::from_default_env())
@@ -101,7 +171,8 @@ fn setup() -> Result<(WorkerGuard, WorkerGuard)> {
                 tracing_subscriber::fmt::Layer::default()
                     .with_writer(color_non_blocking)
                     .with_ansi(true),
-            ),
+            )
+            .with(json_layer),
     )?;
 
     // https://github.com/tokio-rs/tracing/blob/master/examples/examples/panic_hook.rs
@@ -129,10 +200,38 @@ fn setup() -> Result<(WorkerGuard, WorkerGuard)> {
         }
     }));
 
-    Ok((guard, color_guard))
+    Ok((guard, color_guard, json_guard))
+}
+
+pub fn configuration_override() -> Option<PathBuf> {
+    std::env::var_os("KOMOREBI_CONFIG").map(PathBuf::from)
 }
 
 pub fn load_configuration() -> Result<()> {
+    if let Some(config) = configuration_override() {
+        if !config.exists() {
+            return Err(anyhow!("the specified configuration file does not exist"));
+        }
+
+        tracing::info!(
+            "loading configuration file: {}",
+            config
+                .as_os_str()
+                .to_str()
+                .ok_or_else(|| anyhow!("cannot convert path to string"))?
+        );
+
+        let exec = if config.extension().and_then(|ext| ext.to_str()) == Some("ahk2") {
+            "AutoHotkey64.exe"
+        } else {
+            "autohotkey.exe"
+        };
+
+        Command::new(exec).arg(config.as_os_str()).output()?;
+
+        return Ok(());
+    }
+
     let home = dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
 
     let mut config_v1 = home.clone();
@@ -206,7 +305,7 @@ fn main() -> Result<()> {
             }
 
             // File logging worker guard has to have an assignment in the main fn to work
-            let (_guard, _color_guard) = setup()?;
+            let (_guard, _color_guard, _json_guard) = setup()?;
 
             #[cfg(feature = "deadlock_detection")]
             detect_deadlocks();
@@ -246,6 +345,7 @@ fn main() -> Result<()> {
             );
 
             wm.lock().restore_all_windows();
+            wm.lock().restore_window_styles();
             std::process::exit(130);
         }
         _ => Ok(()),
@@ -6,45 +6,106 @@ use color_eyre::Result;
 use getset::CopyGetters;
 use getset::Getters;
 use getset::MutGetters;
+use getset::Setters;
 use serde::Serialize;
 
+use komorebi_core::Layout;
 use komorebi_core::Rect;
 
 use crate::container::Container;
 use crate::ring::Ring;
 use crate::workspace::Workspace;
+use crate::DPI_AWARE_PADDING;
+use crate::MONITOR_INDEX_PREFERENCES;
 
-#[derive(Debug, Clone, Serialize, Getters, CopyGetters, MutGetters)]
+#[derive(Debug, Clone, Serialize, Getters, CopyGetters, MutGetters, Setters)]
 pub struct Monitor {
     #[getset(get_copy = "pub")]
     id: isize,
+    #[getset(get = "pub")]
+    name: String,
+    #[getset(get = "pub")]
     monitor_size: Rect,
     #[getset(get = "pub")]
     work_area_size: Rect,
+    #[getset(get_copy = "pub")]
+    is_primary: bool,
+    #[getset(get_copy = "pub")]
+    scale_factor: f64,
+    #[getset(get = "pub", set = "pub")]
+    work_area_offset: Rect,
+    #[getset(get_copy = "pub", set = "pub")]
+    paused: bool,
+    #[getset(get = "pub", set = "pub")]
+    default_layout: Option<Layout>,
+    #[getset(get_copy = "pub", set = "pub")]
+    max_workspaces: Option<usize>,
     workspaces: Ring<Workspace>,
     #[serde(skip_serializing)]
-    #[getset(get_mut = "pub")]
+    #[getset(get = "pub", get_mut = "pub")]
     workspace_names: HashMap<usize, String>,
 }
 
 impl_ring_elements!(Monitor, Workspace);
 
-pub fn new(id: isize, monitor_size: Rect, work_area_size: Rect) -> Monitor {
+pub fn new(
+    id: isize,
+    name: String,
+    monitor_size: Rect,
+    work_area_size: Rect,
+    is_primary: bool,
+    scale_factor: f64,
+) -> Monitor {
     Monitor {
         id,
+        name,
         monitor_size,
         work_area_size,
+        is_primary,
+        scale_factor,
+        work_area_offset: Rect::default(),
+        paused: false,
+        default_layout: None,
+        max_workspaces: None,
         workspaces: Ring::default(),
         workspace_names: HashMap::default(),
     }
 }
 
+pub fn apply_index_preferences(monitors: &mut VecDeque<Monitor>) {
+    let preferences = MONITOR_INDEX_PREFERENCES.lock();
+    let mut unplaced = std::mem::take(monitors);
+    let mut ordered: Vec<Option<Monitor>> = vec![None; unplaced.len()];
+
+    // Monitors with a preferred index get to claim their slots first...
+    let mut i = 0;
+    while i < unplaced.len() {
+        match preferences.get(unplaced[i].name()) {
+            Some(&idx) if idx < ordered.len() && ordered[idx].is_none() => {
+                ordered[idx] = unplaced.remove(i);
+            }
+            _ => i += 1,
+        }
+    }
+
+    // ...and everything else fills the remaining slots in the order that it was enumerated
+    for slot in &mut ordered {
+        if slot.is_none() {
+            *slot = unplaced.pop_front();
+        }
+    }
+
+    monitors.extend(ordered.into_iter().flatten());
+}
+
 impl Monitor {
     pub fn load_focused_workspace(&mut self) -> Result<()> {
         let focused_idx = self.focused_workspace_idx();
-        for (i, workspace) in self.workspaces_mut().iter_mut().enumerate() {
+        for (i, workspace) in self.workspaces.iter_mut().enumerate() {
             if i == focused_idx {
                 workspace.restore()?;
+            } else if workspace.scratchpad_visible() {
+                workspace.show_as_scratchpad();
             } else {
                 workspace.hide();
             }
@@ -63,11 +124,33 @@ impl Monitor {
         Ok(())
     }
 
-    pub fn ensure_workspace_count(&mut self, ensure_count: usize) {
-        if self.workspaces().len() < ensure_count {
-            self.workspaces_mut()
-                .resize(ensure_count, Workspace::default());
+    // The per-monitor default layout takes precedence over the global default layout
+    fn default_workspace(&self) -> Workspace {
+        let mut workspace = Workspace::default();
+        if let Some(layout) = self.default_layout() {
+            workspace.set_layout(layout.clone());
         }
+
+        workspace
+    }
+
+    pub fn ensure_workspace_count(&mut self, ensure_count: usize) -> Result<()> {
+        if self.workspaces.len() < ensure_count {
+            // Workspaces that already exist are left alone even if they are over the limit
+            if let Some(max) = self.max_workspaces() {
+                if ensure_count > max {
+                    return Err(anyhow!(
+                        "this monitor is limited to a maximum of {} workspaces",
+                        max
+                    ));
+                }
+            }
+
+            let workspace = self.default_workspace();
+            self.workspaces_mut().resize(ensure_count, workspace);
+        }
+
+        Ok(())
     }
 
     #[tracing::instrument(skip(self))]
@@ -76,31 +159,30 @@ impl Monitor {
         target_workspace_idx: usize,
         follow: bool,
     ) -> Result<()> {
-        let workspace = self
-            .focused_workspace_mut()
-            .ok_or_else(|| anyhow!("there is no workspace"))?;
-
-        if workspace.maximized_window().is_some() {
+        if self
+            .focused_workspace()
+            .ok_or_else(|| anyhow!("there is no workspace"))?
+            .maximized_window()
+            .is_some()
+        {
             return Err(anyhow!(
                 "cannot move native maximized window to another monitor or workspace"
             ));
         }
 
-        let container = workspace
+        // Make sure the target workspace can exist before taking the container off the focused one
+        self.ensure_workspace_count(target_workspace_idx + 1)?;
+
+        let container = self
+            .focused_workspace_mut()
+            .ok_or_else(|| anyhow!("there is no workspace"))?
             .remove_focused_container()
             .ok_or_else(|| anyhow!("there is no container"))?;
 
-        let workspaces = self.workspaces_mut();
-
-        let target_workspace = match workspaces.get_mut(target_workspace_idx) {
-            None => {
-                workspaces.resize(target_workspace_idx + 1, Workspace::default());
-                workspaces.get_mut(target_workspace_idx).unwrap()
-            }
-            Some(workspace) => workspace,
-        };
-
-        target_workspace.add_container(container);
+        self.workspaces_mut()
+            .get_mut(target_workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?
+            .add_container(container);
 
         if follow {
             self.focus_workspace(target_workspace_idx)?;
@@ -113,15 +195,8 @@ impl Monitor {
     pub fn focus_workspace(&mut self, idx: usize) -> Result<()> {
         tracing::info!("focusing workspace");
 
-        {
-            let workspaces = self.workspaces_mut();
-
-            if workspaces.get(idx).is_none() {
-                workspaces.resize(idx + 1, Workspace::default());
-            }
-
-            self.workspaces.focus(idx);
-        }
+        self.ensure_workspace_count(idx + 1)?;
+        self.workspaces.focus(idx);
 
         // Always set the latest known name when creating the workspace for the first time
         {
@@ -137,16 +212,49 @@ impl Monitor {
         Ok(())
     }
 
-    pub fn new_workspace_idx(&self) -> usize {
-        self.workspaces().len()
+    pub fn new_workspace_idx(&self) -> Result<usize> {
+        let idx = self.workspaces().len();
+        if let Some(max) = self.max_workspaces() {
+            if idx >= max {
+                return Err(anyhow!(
+                    "this monitor is limited to a maximum of {} workspaces",
+                    max
+                ));
+            }
+        }
+
+        Ok(idx)
+    }
+
+    pub fn work_area_with_offset(&self) -> Rect {
+        let mut work_area = *self.work_area_size();
+        let offset = self.work_area_offset();
+
+        work_area.left += offset.left;
+        work_area.top += offset.top;
+        work_area.right += offset.right;
+        work_area.bottom += offset.bottom;
+
+        work_area
+    }
+
+    // User-specified padding and gaps are only scaled to the DPI of the monitor when DPI-aware
+    // padding has been enabled
+    pub fn padding_scale_factor(&self) -> f64 {
+        if *DPI_AWARE_PADDING.lock() {
+            self.scale_factor
+        } else {
+            1.0
+        }
     }
 
     pub fn update_focused_workspace(&mut self) -> Result<()> {
-        let work_area = *self.work_area_size();
+        let work_area = self.work_area_with_offset();
+        let scale_factor = self.padding_scale_factor();
 
         self.focused_workspace_mut()
             .ok_or_else(|| anyhow!("there is no workspace"))?
-            .update(&work_area)?;
+            .update(&work_area, scale_factor)?;
 
         Ok(())
     }
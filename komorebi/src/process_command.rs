@@ -8,18 +8,37 @@ use std::thread;
 use color_eyre::eyre::anyhow;
 use color_eyre::Result;
 use parking_lot::Mutex;
+use regex::Regex;
 use uds_windows::UnixStream;
 
 use komorebi_core::ApplicationIdentifier;
+use komorebi_core::Layout;
+use komorebi_core::ProcessCommandResponse;
 use komorebi_core::SocketMessage;
+use komorebi_core::WorkspaceRuleCondition;
 
+use crate::window::Window;
 use crate::window_manager;
 use crate::window_manager::WindowManager;
 use crate::windows_api::WindowsApi;
+use crate::AUTO_MANAGE;
+use crate::AUTO_STACK_BY_EXE;
+use crate::CONTAINER_PADDING_RULES;
+use crate::DEFAULT_LAYOUT;
+use crate::DPI_AWARE_PADDING;
 use crate::FLOAT_IDENTIFIERS;
+use crate::IGNORED_TITLES;
+use crate::LAST_EVENT_TIMES;
+use crate::LAYERED_EXE_WHITELIST;
 use crate::MANAGE_IDENTIFIERS;
+use crate::MEMORIZED_POSITIONS;
+use crate::MINIMUM_CONTAINER_SIZE;
+use crate::SUBSCRIPTION_SOCKETS;
+use crate::SUPPRESSED_EVENTS;
+use crate::TRACED_HWNDS;
 use crate::TRAY_AND_MULTI_WINDOW_CLASSES;
 use crate::TRAY_AND_MULTI_WINDOW_EXES;
+use crate::WINEVENT_DEBOUNCE_MS;
 use crate::WORKSPACE_RULES;
 
 #[tracing::instrument]
@@ -49,11 +68,21 @@ pub fn listen_for_commands(wm: Arc<Mutex<WindowManager>>) {
 
 impl WindowManager {
     #[tracing::instrument(skip(self))]
-    pub fn process_command(&mut self, message: SocketMessage) -> Result<()> {
+    pub fn process_command(&mut self, message: SocketMessage) -> Result<Option<serde_json::Value>> {
         self.validate_virtual_desktop_id();
 
+        // Queries don't change anything, so there is no need to notify subscribers about them
+        let notify_subscribers = !message.is_query();
+
+        // Set by queries, and written back on the same connection as the response to the message
+        let mut data = None;
+
         match message {
             SocketMessage::Promote => self.promote_container_to_front()?,
+            SocketMessage::ReorderContainersByExe => self.reorder_containers(Window::exe)?,
+            SocketMessage::ReorderContainersByTitle => self.reorder_containers(Window::title)?,
+            SocketMessage::RotateContainers(direction) => self.rotate_containers(direction)?,
+            SocketMessage::ShuffleContainers => self.shuffle_containers()?,
             SocketMessage::FocusWindow(direction) => {
                 self.focus_container_in_direction(direction)?;
             }
@@ -65,19 +94,111 @@ impl WindowManager {
             SocketMessage::CycleStack(direction) => {
                 self.cycle_container_window_in_direction(direction)?;
             }
+            SocketMessage::MoveWindowInContainerToPosition(position) => {
+                self.move_window_in_container_to_position(position)?;
+            }
+            SocketMessage::SplitContainer(at) => self.split_container(at)?,
+            SocketMessage::MergeContainersInDirection(direction) => {
+                self.merge_containers_in_direction(direction)?;
+            }
+            SocketMessage::SendToNamedScratchpad(name) => {
+                self.send_to_named_scratchpad(name)?;
+            }
+            SocketMessage::ToggleNamedScratchpad(name) => {
+                self.toggle_named_scratchpad(name)?;
+            }
+            SocketMessage::ToggleWorkspaceScratchpad(monitor_idx, workspace_idx) => {
+                self.toggle_workspace_scratchpad(monitor_idx, workspace_idx)?;
+            }
             SocketMessage::ToggleFloat => self.toggle_float()?,
+            SocketMessage::CenterFloatingWindow => self.center_floating_window(None)?,
+            SocketMessage::MemorizeWindowPosition => {
+                let window = Window {
+                    hwnd: WindowsApi::foreground_window()?,
+                };
+
+                let rect = WindowsApi::window_rect(window.hwnd())?;
+                MEMORIZED_POSITIONS.lock().insert(window.hwnd, rect);
+            }
+            SocketMessage::RestoreWindowPosition => {
+                let window = Window {
+                    hwnd: WindowsApi::foreground_window()?,
+                };
+
+                let rect = *MEMORIZED_POSITIONS
+                    .lock()
+                    .get(&window.hwnd)
+                    .ok_or_else(|| anyhow!("there is no memorized position for this window"))?;
+
+                // The memorized rect already includes the invisible border, so it is applied as-is
+                // instead of going through Window::set_position
+                WindowsApi::position_window(window.hwnd(), &rect, true)?;
+            }
+            SocketMessage::PinFocusedWindowToWorkspace => self.pin_focused_window_to_workspace()?,
+            SocketMessage::CenterFloatingWindowOnMonitor(monitor_idx) => {
+                self.center_floating_window(Option::from(monitor_idx))?;
+            }
+            SocketMessage::MoveFloatingWindowToTile(container_idx) => {
+                self.move_floating_window_to_tile(container_idx)?;
+            }
+            SocketMessage::TileAllFloatingWindows => self.tile_all_floating_windows()?,
+            SocketMessage::FloatAllContainerWindows => self.float_all_container_windows()?,
             SocketMessage::ToggleMonocle => self.toggle_monocle()?,
+            SocketMessage::CycleMonocle(direction) => self.cycle_monocle(direction)?,
             SocketMessage::ToggleMaximize => self.toggle_maximize()?,
+            SocketMessage::ToggleSoftMaximize => self.toggle_soft_maximize()?,
+            SocketMessage::ToggleWindowBorder => self.toggle_window_border()?,
+            SocketMessage::ToggleFocusBorder => self.toggle_focus_border()?,
+            SocketMessage::SetFocusBorderColor(r, g, b) => self.set_focus_border_color(r, g, b)?,
+            SocketMessage::SetUnfocusBorderColor(r, g, b) => {
+                self.set_unfocus_border_color(r, g, b)?;
+            }
+            SocketMessage::ToggleTitleBars => self.toggle_title_bars()?,
+            SocketMessage::ToggleWindowDecoration => self.toggle_window_decoration()?,
+            SocketMessage::FocusWindowUnderCursor => self.focus_window_under_cursor()?,
+            SocketMessage::FocusFloatingWindow(direction) => {
+                self.focus_floating_window(direction)?;
+            }
+            SocketMessage::FocusTiledWindow => self.focus_tiled_window()?,
+            SocketMessage::CycleContainerAcrossWorkspaces(direction) => {
+                self.cycle_container_across_workspaces(direction)?;
+            }
             SocketMessage::ContainerPadding(monitor_idx, workspace_idx, size) => {
                 self.set_container_padding(monitor_idx, workspace_idx, size)?;
             }
             SocketMessage::WorkspacePadding(monitor_idx, workspace_idx, size) => {
                 self.set_workspace_padding(monitor_idx, workspace_idx, size)?;
             }
-            SocketMessage::WorkspaceRule(_, id, monitor_idx, workspace_idx) => {
+            SocketMessage::SetWorkspacePaddingFromEdge(monitor_idx, workspace_idx, padding) => {
+                self.set_workspace_padding_from_edge(monitor_idx, workspace_idx, padding)?;
+            }
+            SocketMessage::SetWorkspaceGap(monitor_idx, workspace_idx, size) => {
+                self.set_workspace_gap(monitor_idx, workspace_idx, size)?;
+            }
+            SocketMessage::SetContainerPaddingForMonitor(monitor_idx, size) => {
+                self.set_container_padding_for_monitor(monitor_idx, size)?;
+            }
+            SocketMessage::SetWorkspacePaddingForMonitor(monitor_idx, size) => {
+                self.set_workspace_padding_for_monitor(monitor_idx, size)?;
+            }
+            SocketMessage::SetGlobalContainerPadding(size) => {
+                self.set_global_container_padding(size)?;
+            }
+            SocketMessage::SetGlobalWorkspacePadding(size) => {
+                self.set_global_workspace_padding(size)?;
+            }
+            SocketMessage::WorkspaceRule(_, id, mapping) => {
+                {
+                    let mut workspace_rules = WORKSPACE_RULES.lock();
+                    workspace_rules.insert(id, (mapping, WorkspaceRuleCondition::Always));
+                }
+
+                self.enforce_workspace_rules()?;
+            }
+            SocketMessage::WorkspaceRuleWithCondition(_, id, mapping, condition) => {
                 {
                     let mut workspace_rules = WORKSPACE_RULES.lock();
-                    workspace_rules.insert(id, (monitor_idx, workspace_idx));
+                    workspace_rules.insert(id, (mapping, condition));
                 }
 
                 self.enforce_workspace_rules()?;
@@ -103,6 +224,9 @@ impl WindowManager {
             SocketMessage::MoveContainerToWorkspaceNumber(workspace_idx) => {
                 self.move_container_to_workspace(workspace_idx, true)?;
             }
+            SocketMessage::MoveContainerToNextNonEmptyWorkspace(direction) => {
+                self.move_container_to_next_non_empty_workspace(direction)?;
+            }
             SocketMessage::MoveContainerToMonitorNumber(monitor_idx) => {
                 self.move_container_to_monitor(monitor_idx, true)?;
             }
@@ -110,16 +234,48 @@ impl WindowManager {
                 tracing::info!("pausing");
                 self.is_paused = !self.is_paused;
             }
+            SocketMessage::TogglePauseMonitor(monitor_idx) => {
+                self.toggle_pause_monitor(monitor_idx)?;
+            }
             SocketMessage::ToggleTiling => {
                 self.toggle_tiling()?;
             }
+            SocketMessage::ToggleGapless => {
+                self.toggle_gapless()?;
+            }
             SocketMessage::FocusMonitorNumber(monitor_idx) => {
                 self.focus_monitor(monitor_idx)?;
                 self.update_focused_workspace(true)?;
             }
+            SocketMessage::FocusMonitorInDirection(direction) => {
+                let monitor_idx = self
+                    .monitor_idx_in_direction(direction)
+                    .ok_or_else(|| anyhow!("there is no monitor in this direction"))?;
+
+                self.focus_monitor(monitor_idx)?;
+                self.update_focused_workspace(true)?;
+            }
+            SocketMessage::FocusPrimaryMonitor => {
+                let monitor_idx = self
+                    .primary_monitor_idx()
+                    .ok_or_else(|| anyhow!("there is no primary monitor"))?;
+
+                self.focus_monitor(monitor_idx)?;
+                self.update_focused_workspace(true)?;
+            }
+            SocketMessage::FocusCycleMonitor(direction) => {
+                let monitor_idx =
+                    direction.next_idx(self.focused_monitor_idx(), self.monitors().len());
+
+                self.focus_monitor(monitor_idx)?;
+                self.update_focused_workspace(true)?;
+            }
             SocketMessage::Retile => {
+                Layout::clear_custom_layout_cache();
+
                 for monitor in self.monitors_mut() {
-                    let work_area = *monitor.work_area_size();
+                    let work_area = monitor.work_area_with_offset();
+                    let scale_factor = monitor.padding_scale_factor();
                     let workspace = monitor
                         .focused_workspace_mut()
                         .ok_or_else(|| anyhow!("there is no workspace"))?;
@@ -129,49 +285,154 @@ impl WindowManager {
                         *resize = None;
                     }
 
-                    workspace.update(&work_area)?;
+                    workspace.update(&work_area, scale_factor)?;
                 }
             }
             SocketMessage::FlipLayout(layout_flip) => self.flip_layout(layout_flip)?,
-            SocketMessage::ChangeLayout(layout) => self.change_workspace_layout(layout)?,
+            SocketMessage::ChangeLayout(layout) => {
+                layout.validate()?;
+                self.change_workspace_layout(layout)?;
+            }
+            SocketMessage::UndoLayoutChange => self.undo_layout_change()?,
+            SocketMessage::UndoFlipLayout => self.undo_flip_layout()?,
+            SocketMessage::SetThreeColumnRatio(main_ratio, secondary_ratio) => {
+                self.set_three_column_ratio(main_ratio, secondary_ratio)?;
+            }
+            SocketMessage::SetColumnsMainRatio(ratio) => self.set_columns_main_ratio(ratio)?,
             SocketMessage::WorkspaceTiling(monitor_idx, workspace_idx, tile) => {
                 self.set_workspace_tiling(monitor_idx, workspace_idx, tile)?;
             }
+            SocketMessage::WorkspaceMouseFollowsFocus(monitor_idx, workspace_idx, enable) => {
+                self.set_workspace_mouse_follows_focus(monitor_idx, workspace_idx, enable)?;
+            }
+            SocketMessage::SetWorkspaceDefaultLayout(monitor_idx, layout) => {
+                layout.validate()?;
+                self.set_workspace_default_layout(monitor_idx, layout)?;
+            }
+            SocketMessage::SetGlobalDefaultLayout(layout) => {
+                layout.validate()?;
+                *DEFAULT_LAYOUT.lock() = layout;
+            }
             SocketMessage::WorkspaceLayout(monitor_idx, workspace_idx, layout) => {
+                layout.validate()?;
                 self.set_workspace_layout(monitor_idx, workspace_idx, layout)?;
             }
+            SocketMessage::WorkspaceLayoutFlip(monitor_idx, workspace_idx, layout_flip) => {
+                self.set_workspace_layout_flip(monitor_idx, workspace_idx, layout_flip)?;
+            }
             SocketMessage::FocusWorkspaceNumber(workspace_idx) => {
                 self.focus_workspace(workspace_idx)?;
             }
+            SocketMessage::SetFocusedMonitorWorkspace(monitor_idx, workspace_idx) => {
+                self.set_focused_monitor_workspace(monitor_idx, workspace_idx)?;
+            }
             SocketMessage::Stop => {
                 tracing::info!(
                     "received stop command, restoring all hidden windows and terminating process"
                 );
                 self.restore_all_windows();
+                self.restore_window_styles();
                 std::process::exit(0)
             }
+            SocketMessage::StopWithSaveState => {
+                tracing::info!(
+                    "received stop command, saving state, restoring all hidden windows and terminating process"
+                );
+                self.save_state()?;
+                self.restore_all_windows();
+                self.restore_window_styles();
+                std::process::exit(0)
+            }
+            SocketMessage::RestoreState => {
+                self.restore_state()?;
+            }
             SocketMessage::EnsureWorkspaces(monitor_idx, workspace_count) => {
                 self.ensure_workspaces_for_monitor(monitor_idx, workspace_count)?;
             }
+            SocketMessage::SetMonitorWorkAreaOffset(monitor_idx, offset) => {
+                self.set_monitor_work_area_offset(monitor_idx, offset)?;
+            }
+            SocketMessage::MapMonitorToIndex(name, idx) => {
+                self.map_monitor_to_index(name, idx)?;
+            }
+            SocketMessage::EnsureWorkspacesForAllMonitors(workspace_count) => {
+                self.ensure_workspaces_for_all_monitors(workspace_count)?;
+            }
+            SocketMessage::SetMaxWorkspaces(monitor_idx, max) => {
+                self.set_max_workspaces(monitor_idx, max)?;
+            }
             SocketMessage::NewWorkspace => {
-                self.new_workspace()?;
+                self.new_workspace(None)?;
+            }
+            SocketMessage::NewNamedWorkspace(name) => {
+                self.new_workspace(name)?;
             }
             SocketMessage::WorkspaceName(monitor_idx, workspace_idx, name) => {
                 self.set_workspace_name(monitor_idx, workspace_idx, name)?;
             }
             SocketMessage::State => {
-                let state = serde_json::to_string_pretty(&window_manager::State::from(self))?;
-                let mut socket =
-                    dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
-                socket.push("komorebic.sock");
-                let socket = socket.as_path();
+                let state = window_manager::State::from(&mut *self);
+                data = Option::from(serde_json::to_value(state)?);
+            }
+            // Answered in read_command so that it gets a response even while paused
+            SocketMessage::Ping => {}
+            SocketMessage::GetContainerForWindow(hwnd) => {
+                let location = self.window_location(hwnd);
+                data = Option::from(serde_json::to_value(location)?);
+            }
+            SocketMessage::IsManaged(hwnd) => {
+                let status = self.managed_status(hwnd);
+                data = Option::from(serde_json::to_value(status)?);
+            }
+            SocketMessage::GetProcessIdForWindow(hwnd) => {
+                let process_id = self.process_id_for_window(hwnd);
+                data = Option::from(serde_json::to_value(process_id)?);
+            }
+            SocketMessage::GetFocusedWindowGeometry => {
+                let window = Window {
+                    hwnd: WindowsApi::foreground_window()?,
+                };
 
-                let mut stream = UnixStream::connect(&socket)?;
-                stream.write_all(state.as_bytes())?;
+                let geometry = WindowsApi::window_rect(window.hwnd())?;
+                data = Option::from(serde_json::to_value(geometry)?);
+            }
+            SocketMessage::GetFocusedWorkspaceIndex => {
+                let index = self.focused_workspace_index()?;
+                data = Option::from(serde_json::to_value(index)?);
+            }
+            SocketMessage::DumpWindowTree => {
+                data = Option::from(serde_json::to_value(self.window_tree())?);
+            }
+            SocketMessage::AddSubscriber(socket) => {
+                let mut subscribers = SUBSCRIPTION_SOCKETS.lock();
+                if !subscribers.contains(&socket) {
+                    subscribers.push(socket);
+                }
+            }
+            SocketMessage::Version => {
+                data = Option::from(serde_json::to_value(env!("CARGO_PKG_VERSION"))?);
+            }
+            SocketMessage::MonitorInfo => {
+                let information = self.monitor_information()?;
+                data = Option::from(serde_json::to_value(information)?);
+            }
+            SocketMessage::WorkspaceWindowCounts => {
+                let counts = self.workspace_window_counts();
+                data = Option::from(serde_json::to_value(counts)?);
             }
             SocketMessage::ResizeWindow(direction, sizing) => {
                 self.resize_window(direction, sizing, Option::from(50))?;
             }
+            SocketMessage::ResizeWindowAbsolute(width, height) => {
+                self.resize_window_absolute(width, height)?;
+            }
+            SocketMessage::SetMinimumContainerSize(width, height) => {
+                *MINIMUM_CONTAINER_SIZE.lock() = (width, height);
+            }
+            SocketMessage::LockContainerAspectRatio(width, height) => {
+                self.set_container_aspect_ratio(Option::from((width, height)))?;
+            }
+            SocketMessage::UnlockContainerAspectRatio => self.set_container_aspect_ratio(None)?,
             SocketMessage::FocusFollowsMouse(enable) => {
                 if enable {
                     WindowsApi::enable_focus_follows_mouse()?;
@@ -179,6 +440,55 @@ impl WindowManager {
                     WindowsApi::disable_focus_follows_mouse()?;
                 }
             }
+            SocketMessage::ToggleAutoFullscreenPause(enable) => {
+                self.set_auto_fullscreen_pause(enable)?;
+            }
+            SocketMessage::ToggleMonocleIncludeFloating(enable) => {
+                self.set_monocle_include_floating(enable);
+            }
+            SocketMessage::EnableVirtualDesktopIntegration(enable) => {
+                self.set_virtual_desktop_integration(enable);
+            }
+            SocketMessage::ToggleDynamicWorkspaceNaming(enable) => {
+                self.set_dynamic_workspace_naming(enable)?;
+            }
+            SocketMessage::ToggleDpiAwarePadding(enable) => {
+                *DPI_AWARE_PADDING.lock() = enable;
+
+                for monitor in self.monitors_mut() {
+                    monitor.update_focused_workspace()?;
+                }
+            }
+            SocketMessage::ToggleAutoManage(enable) => {
+                *AUTO_MANAGE.lock() = enable;
+            }
+            SocketMessage::ToggleAutoStackByExe(enable) => {
+                *AUTO_STACK_BY_EXE.lock() = enable;
+            }
+            SocketMessage::SetWindowOpacity(hwnd, opacity) => {
+                let window = Window { hwnd };
+
+                // Layered windows are ignored unless their exe is whitelisted, so we need to make
+                // sure that the window doesn't stop being managed once it becomes translucent
+                let exe = window.exe()?;
+                {
+                    let mut layered_exe_whitelist = LAYERED_EXE_WHITELIST.lock();
+                    if !layered_exe_whitelist.contains(&exe) {
+                        layered_exe_whitelist.push(exe);
+                    }
+                }
+
+                window.set_opacity(opacity)?;
+            }
+            SocketMessage::TraceWindow(hwnd) => {
+                let mut traced_hwnds = TRACED_HWNDS.lock();
+                if !traced_hwnds.contains(&hwnd) {
+                    traced_hwnds.push(hwnd);
+                }
+            }
+            SocketMessage::StopTracing => {
+                TRACED_HWNDS.lock().clear();
+            }
             SocketMessage::ToggleFocusFollowsMouse => {
                 if WindowsApi::focus_follows_mouse()? {
                     WindowsApi::disable_focus_follows_mouse()?;
@@ -205,40 +515,95 @@ impl WindowManager {
                         classes.push(id);
                     }
                 }
-                ApplicationIdentifier::Title => {}
+                ApplicationIdentifier::Title | ApplicationIdentifier::Path => {}
             },
+            SocketMessage::IdentifyIgnoreTitle(pattern) => {
+                let regex = Regex::new(&pattern)?;
+
+                let mut ignored_titles = IGNORED_TITLES.lock();
+                if !ignored_titles.iter().any(|r| r.as_str() == regex.as_str()) {
+                    ignored_titles.push(regex);
+                }
+            }
+            SocketMessage::SetApplicationContainerPadding(_, id, padding) => {
+                {
+                    let mut container_padding_rules = CONTAINER_PADDING_RULES.lock();
+                    container_padding_rules.insert(id, padding);
+                }
+
+                self.update_focused_workspace(false)?;
+            }
+            SocketMessage::SuppressEventForExe(event_type, exe) => {
+                let mut suppressed_events = SUPPRESSED_EVENTS.lock();
+                suppressed_events.entry(exe).or_default().insert(event_type);
+            }
+            SocketMessage::SetEventDebounce(ms) => {
+                *WINEVENT_DEBOUNCE_MS.lock() = ms;
+                LAST_EVENT_TIMES.lock().clear();
+            }
             SocketMessage::ManageFocusedWindow => {
                 self.manage_focused_window()?;
             }
             SocketMessage::UnmanageFocusedWindow => {
                 self.unmanage_focused_window()?;
             }
+            SocketMessage::UnmanageWindow(hwnd) => {
+                self.unmanage_window(hwnd)?;
+            }
+        }
+
+        if notify_subscribers {
+            self.notify_subscribers()?;
         }
 
         tracing::info!("processed");
-        Ok(())
+        Ok(data)
     }
 
     #[tracing::instrument(skip(self, stream))]
     pub fn read_commands(&mut self, stream: UnixStream) -> Result<()> {
+        let mut responder = stream.try_clone()?;
         let stream = BufReader::new(stream);
         for line in stream.lines() {
-            let message = SocketMessage::from_str(&line?)?;
+            let result = self.read_command(&line?);
 
-            if self.is_paused {
-                if let SocketMessage::TogglePause = message {
-                    tracing::info!("resuming");
-                    self.is_paused = !self.is_paused;
-                    return Ok(());
-                }
+            let response = match &result {
+                Ok(response) => response.clone(),
+                Err(error) => ProcessCommandResponse::error(error.to_string()),
+            };
 
-                tracing::trace!("ignoring while paused");
-                return Ok(());
+            // Anything writing to the socket directly without waiting for a response will have
+            // already closed the connection by now, which is fine
+            if let Err(error) = writeln!(responder, "{}", serde_json::to_string(&response)?) {
+                tracing::debug!("could not send response: {}", error);
             }
 
-            self.process_command(message)?;
+            result?;
         }
 
         Ok(())
     }
+
+    fn read_command(&mut self, line: &str) -> Result<ProcessCommandResponse> {
+        let message = SocketMessage::from_str(line)?;
+
+        if matches!(message, SocketMessage::Ping) {
+            return Ok(ProcessCommandResponse::pong());
+        }
+
+        if self.is_paused {
+            if let SocketMessage::TogglePause = message {
+                tracing::info!("resuming");
+                self.is_paused = !self.is_paused;
+                return Ok(ProcessCommandResponse::ok());
+            }
+
+            tracing::trace!("ignoring while paused");
+            return Ok(ProcessCommandResponse::ok());
+        }
+
+        Ok(self
+            .process_command(message)?
+            .map_or_else(ProcessCommandResponse::ok, ProcessCommandResponse::data))
+    }
 }
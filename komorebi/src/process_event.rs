@@ -1,9 +1,13 @@
 use std::fs::OpenOptions;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use color_eyre::eyre::anyhow;
 use color_eyre::Result;
+use crossbeam_channel::after;
+use crossbeam_channel::never;
 use crossbeam_channel::select;
 use parking_lot::Mutex;
 
@@ -14,9 +18,15 @@ use komorebi_core::Sizing;
 use crate::window_manager::WindowManager;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::windows_api::WindowsApi;
+use crate::winevent::WinEvent;
+use crate::AUTO_STACK_BY_EXE;
+use crate::DEBOUNCED_EVENTS;
 use crate::HIDDEN_HWNDS;
+use crate::LAST_EVENT_TIMES;
+use crate::SUPPRESSED_EVENTS;
 use crate::TRAY_AND_MULTI_WINDOW_CLASSES;
 use crate::TRAY_AND_MULTI_WINDOW_EXES;
+use crate::WINEVENT_DEBOUNCE_MS;
 
 #[tracing::instrument]
 pub fn listen_for_events(wm: Arc<Mutex<WindowManager>>) {
@@ -25,6 +35,12 @@ pub fn listen_for_events(wm: Arc<Mutex<WindowManager>>) {
     thread::spawn(move || {
         tracing::info!("listening");
         loop {
+            // Wake up when the debounce window of the next deferred event has passed, so that it
+            // is processed even if no other events come in after it
+            let debounce_timer = next_debounce_deadline().map_or_else(never, |deadline| {
+                after(deadline.saturating_duration_since(Instant::now()))
+            });
+
             select! {
                 recv(receiver) -> mut maybe_event => {
                     if let Ok(event) = maybe_event.as_mut() {
@@ -34,20 +50,129 @@ pub fn listen_for_events(wm: Arc<Mutex<WindowManager>>) {
                         }
                     }
                 }
+                recv(debounce_timer) -> _ => {
+                    wm.lock().process_debounced_events();
+                }
             }
         }
     });
 }
 
+fn next_debounce_deadline() -> Option<Instant> {
+    let debounce = Duration::from_millis(*WINEVENT_DEBOUNCE_MS.lock());
+    let last_event_times = LAST_EVENT_TIMES.lock();
+
+    DEBOUNCED_EVENTS
+        .lock()
+        .keys()
+        .map(|key| {
+            last_event_times
+                .get(key)
+                .map_or_else(Instant::now, |last| *last + debounce)
+        })
+        .min()
+}
+
 impl WindowManager {
+    #[tracing::instrument(skip(self))]
+    pub fn process_debounced_events(&mut self) {
+        let debounce = Duration::from_millis(*WINEVENT_DEBOUNCE_MS.lock());
+        let now = Instant::now();
+
+        let mut due = vec![];
+        {
+            let last_event_times = LAST_EVENT_TIMES.lock();
+            DEBOUNCED_EVENTS.lock().retain(|key, event| {
+                let waiting = matches!(
+                    last_event_times.get(key),
+                    Some(last) if now.duration_since(*last) < debounce
+                );
+
+                if !waiting {
+                    due.push(event.clone());
+                }
+
+                waiting
+            });
+        }
+
+        for mut event in due {
+            if let Err(error) = self.process_event(&mut event) {
+                tracing::error!("{}", error);
+            }
+        }
+    }
+
     #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     #[tracing::instrument(skip(self))]
     pub fn process_event(&mut self, event: &mut WindowManagerEvent) -> Result<()> {
+        // This has to happen before we check if we are paused, otherwise we would never notice
+        // when a fullscreen window that paused the window manager goes away
+        if self.auto_fullscreen_pause {
+            if let Err(error) = self.handle_fullscreen_pause(event) {
+                tracing::error!("could not handle fullscreen pause: {}", error);
+            }
+        }
+
         if self.is_paused {
             tracing::trace!("ignoring while paused");
             return Ok(());
         }
 
+        if let Some(monitor_idx) = self.monitor_idx_from_window(event.window()) {
+            if let Some(monitor) = self.monitors().get(monitor_idx) {
+                if monitor.paused() {
+                    tracing::trace!("ignoring while monitor is paused");
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some(event_type) = event.event_type() {
+            if !SUPPRESSED_EVENTS.lock().is_empty() {
+                if let Ok(exe) = event.window().exe() {
+                    let suppressed = SUPPRESSED_EVENTS
+                        .lock()
+                        .get(&exe)
+                        .map(|event_types| event_types.contains(&event_type));
+
+                    if suppressed == Some(true) {
+                        tracing::trace!("ignoring suppressed event");
+                        return Ok(());
+                    }
+                }
+            }
+
+            // Only the first event of each type for a window is processed straight away within the
+            // debounce window, so that bursts of events don't trigger a retile for every single
+            // one. The latest of the rest is processed once the window has passed, so that the
+            // final event of a burst, such as the end of a drag, is never lost
+            let debounce_ms = *WINEVENT_DEBOUNCE_MS.lock();
+            if debounce_ms > 0 {
+                let debounce = Duration::from_millis(debounce_ms);
+                let key = (event_type, event.window().hwnd);
+                let now = Instant::now();
+                let last = LAST_EVENT_TIMES.lock().get(&key).copied();
+
+                if matches!(last, Some(last) if now.duration_since(last) < debounce) {
+                    tracing::trace!("deferring debounced event");
+                    DEBOUNCED_EVENTS.lock().insert(key, event.clone());
+                    return Ok(());
+                }
+
+                LAST_EVENT_TIMES.lock().insert(key, now);
+                DEBOUNCED_EVENTS.lock().remove(&key);
+            }
+        }
+
+        // Windows that we don't know about yet are treated as if they were just shown, which
+        // catches apps like Firefox that only send title changes on launch
+        if let WindowManagerEvent::TitleChange(window, _) = event {
+            if !self.managed_status(window.hwnd).managed {
+                *event = WindowManagerEvent::Show(WinEvent::ObjectNameChange, *window);
+            }
+        }
+
         self.validate_virtual_desktop_id();
 
         // Make sure we have the most recently focused monitor from any event
@@ -65,11 +190,12 @@ impl WindowManager {
         }
 
         for (i, monitor) in self.monitors_mut().iter_mut().enumerate() {
-            let work_area = *monitor.work_area_size();
+            let work_area = monitor.work_area_with_offset();
+            let scale_factor = monitor.padding_scale_factor();
             for (j, workspace) in monitor.workspaces_mut().iter_mut().enumerate() {
                 let reaped_orphans = workspace.reap_orphans()?;
                 if reaped_orphans.0 > 0 || reaped_orphans.1 > 0 {
-                    workspace.update(&work_area)?;
+                    workspace.update(&work_area, scale_factor)?;
                     tracing::info!(
                         "reaped {} orphan window(s) and {} orphaned container(s) on monitor: {}, workspace: {}",
                         reaped_orphans.0,
@@ -90,6 +216,20 @@ impl WindowManager {
             return Ok(());
         }
 
+        // Scratchpad windows are shown and hidden by us, and should never end up in a layout
+        let hwnd = event.window().hwnd;
+        if self.scratchpad_contains_window(hwnd) {
+            if matches!(
+                event,
+                WindowManagerEvent::Destroy(..) | WindowManagerEvent::Unmanage(..)
+            ) {
+                self.remove_window_from_scratchpads(hwnd);
+            }
+
+            tracing::trace!("ignoring event for scratchpad window");
+            return Ok(());
+        }
+
         match event {
             WindowManagerEvent::Minimize(_, window)
             | WindowManagerEvent::Destroy(_, window)
@@ -145,16 +285,9 @@ impl WindowManager {
                     .focus_container_by_window(window.hwnd)?;
             }
             WindowManagerEvent::Show(_, window) | WindowManagerEvent::Manage(window) => {
-                let mut switch_to = None;
-                for (i, monitors) in self.monitors().iter().enumerate() {
-                    for (j, workspace) in monitors.workspaces().iter().enumerate() {
-                        if workspace.contains_window(window.hwnd) {
-                            switch_to = Some((i, j));
-                        }
-                    }
-                }
-
-                if let Some((known_monitor_idx, known_workspace_idx)) = switch_to {
+                if let Some((known_monitor_idx, known_workspace_idx)) =
+                    self.workspace_for_window(window.hwnd)
+                {
                     if self.focused_monitor_idx() != known_monitor_idx
                         || self
                             .focused_monitor()
@@ -162,6 +295,15 @@ impl WindowManager {
                             .focused_workspace_idx()
                             != known_workspace_idx
                     {
+                        // Windows on a workspace shown as a scratchpad are shown over the focused
+                        // workspace without switching to their own
+                        if matches!(
+                            self.workspace_by_idx(known_monitor_idx, known_workspace_idx),
+                            Some(workspace) if workspace.scratchpad_visible()
+                        ) {
+                            return Ok(());
+                        }
+
                         self.focus_monitor(known_monitor_idx)?;
                         self.focus_workspace(known_workspace_idx)?;
                         return Ok(());
@@ -192,7 +334,20 @@ impl WindowManager {
                 let workspace = self.focused_workspace_mut()?;
 
                 if !workspace.contains_window(window.hwnd) {
-                    workspace.new_container_for_window(*window);
+                    let stack_idx = if *AUTO_STACK_BY_EXE.lock() {
+                        window
+                            .exe()
+                            .ok()
+                            .and_then(|exe| workspace.container_idx_for_exe(&exe))
+                    } else {
+                        None
+                    };
+
+                    match stack_idx {
+                        Some(idx) => workspace.add_window_to_container_at_idx(idx, *window)?,
+                        None => workspace.new_container_for_window(*window),
+                    }
+
                     self.update_focused_workspace(false)?;
                 }
             }
@@ -206,6 +361,9 @@ impl WindowManager {
                     return Ok(());
                 }
 
+                // The window may have been dragged across monitors with a different DPI
+                window.invalidate_border_rect();
+
                 let focused_idx = workspace.focused_container_idx();
                 let old_position = *workspace
                     .latest_layout()
@@ -285,6 +443,15 @@ impl WindowManager {
                     self.update_focused_workspace(false)?;
                 }
             }
+            WindowManagerEvent::TitleChange(window, old_title) => {
+                // Workspace rules have already been enforced for every event by this point, which
+                // will pick up any title rules that match the new title
+                tracing::info!(
+                    "title changed from \"{}\" to \"{}\"",
+                    old_title,
+                    window.title()?
+                );
+            }
             WindowManagerEvent::MouseCapture(..) => {}
         };
 
@@ -293,6 +460,14 @@ impl WindowManager {
             window.center(&self.focused_monitor_work_area()?)?;
         }
 
+        if self.focus_border {
+            self.update_focus_border()?;
+        }
+
+        if self.hide_title_bars {
+            self.update_title_bars()?;
+        }
+
         tracing::trace!("updating list of known hwnds");
         let mut known_hwnds = vec![];
         for monitor in self.monitors() {
@@ -315,6 +490,9 @@ impl WindowManager {
             .open(hwnd_json)?;
 
         serde_json::to_writer_pretty(&file, &known_hwnds)?;
+
+        self.notify_subscribers()?;
+
         tracing::info!("processed: {}", event.window().to_string());
         Ok(())
     }
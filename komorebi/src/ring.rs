@@ -1,18 +1,25 @@
+use std::collections::vec_deque::Iter;
+use std::collections::vec_deque::IterMut;
 use std::collections::VecDeque;
+use std::ops::Index;
+use std::ops::IndexMut;
 
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Ring<T> {
     elements: VecDeque<T>,
-    focused: usize,
+    // Only ever updated through focus(), but exposed so that callers doing multi-step operations
+    // can read a stable index without going through a method call every time
+    #[serde(rename = "focused")]
+    pub focused_idx: usize,
 }
 
 impl<T> Default for Ring<T> {
     fn default() -> Self {
         Self {
             elements: VecDeque::default(),
-            focused: 0,
+            focused_idx: 0,
         }
     }
 }
@@ -27,24 +34,74 @@ impl<T> Ring<T> {
     }
 
     pub fn focus(&mut self, idx: usize) {
-        self.focused = idx;
+        self.focused_idx = idx;
     }
 
     pub fn focused(&self) -> Option<&T> {
-        self.elements.get(self.focused)
+        self.elements.get(self.focused_idx)
     }
 
     pub const fn focused_idx(&self) -> usize {
-        self.focused
+        self.focused_idx
     }
 
     pub fn focused_mut(&mut self) -> Option<&mut T> {
-        self.elements.get_mut(self.focused)
+        self.elements.get_mut(self.focused_idx)
     }
 
     pub fn swap(&mut self, i: usize, j: usize) {
         self.elements.swap(i, j);
     }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    // The VecDeque iterators are both double-ended and exact-sized, so callers can use .rev()
+    // and .len() on them without collecting first
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.elements.iter_mut()
+    }
+}
+
+impl<T> Index<usize> for Ring<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.elements[idx]
+    }
+}
+
+impl<T> IndexMut<usize> for Ring<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.elements[idx]
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ring<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Ring<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter_mut()
+    }
 }
 
 macro_rules! impl_ring_elements {
@@ -65,7 +122,7 @@ macro_rules! impl_ring_elements {
                 }
 
                 pub const fn [<focused_ $element:lower _idx>](&self) -> usize {
-                    self.[<$element:lower s>].focused_idx()
+                    self.[<$element:lower s>].focused_idx
                 }
 
                 pub fn [<focused_ $element:lower _mut>](&mut self) -> Option<&mut $element> {
@@ -75,3 +132,73 @@ macro_rules! impl_ring_elements {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(elements: &[i32]) -> Ring<i32> {
+        let mut ring = Ring::default();
+        ring.elements_mut().extend(elements);
+        ring
+    }
+
+    #[test]
+    fn index_and_iterate() {
+        let mut ring = ring(&[1, 2, 3]);
+        ring[1] = 5;
+
+        assert_eq!(ring[1], 5);
+        assert_eq!(ring.iter().len(), 3);
+        assert_eq!(
+            ring.iter().rev().copied().collect::<Vec<_>>(),
+            vec![3, 5, 1]
+        );
+
+        for element in &mut ring {
+            *element *= 2;
+        }
+
+        let mut doubled = vec![];
+        for element in &ring {
+            doubled.push(*element);
+        }
+
+        assert_eq!(doubled, vec![2, 10, 6]);
+    }
+
+    #[test]
+    fn focus_is_reflected_by_the_field_and_accessors() {
+        let mut ring = ring(&[1, 2, 3]);
+
+        assert_eq!(ring.focused_idx, 0);
+        assert_eq!(ring.focused(), Some(&1));
+
+        ring.focus(2);
+
+        assert_eq!(ring.focused_idx, 2);
+        assert_eq!(ring.focused_idx(), 2);
+        assert_eq!(ring.focused(), Some(&3));
+
+        if let Some(focused) = ring.focused_mut() {
+            *focused = 4;
+        }
+
+        assert_eq!(ring[2], 4);
+    }
+
+    #[test]
+    fn focus_out_of_bounds() {
+        let mut ring = ring(&[1, 2]);
+        ring.focus(2);
+
+        assert_eq!(ring.focused(), None);
+        assert_eq!(ring.focused_mut(), None);
+
+        let mut empty: Ring<i32> = Ring::default();
+
+        assert!(empty.is_empty());
+        assert_eq!(empty.focused(), None);
+        assert_eq!(empty.focused_mut(), None);
+    }
+}
@@ -15,10 +15,15 @@ use crate::styles::GwlExStyle;
 use crate::styles::GwlStyle;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::windows_api::WindowsApi;
+use crate::BORDER_RECT_CACHE;
+use crate::CONTAINER_PADDING_RULES;
+use crate::DECORATIONLESS_HWNDS;
 use crate::FLOAT_IDENTIFIERS;
 use crate::HIDDEN_HWNDS;
+use crate::IGNORED_TITLES;
 use crate::LAYERED_EXE_WHITELIST;
 use crate::MANAGE_IDENTIFIERS;
+use crate::TITLE_BARLESS_HWNDS;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Window {
@@ -86,33 +91,8 @@ impl Window {
     }
 
     pub fn set_position(&mut self, layout: &Rect, top: bool) -> Result<()> {
-        // NOTE: This is how the border variable below was calculated; every time this code was
-        // run on any window in any position, the generated border was always the same, so I am
-        // hard coding the border Rect to avoid two calls to set_window_pos and making the screen
-        // flicker on container/window movement. Still not 100% sure if this is DPI-aware.
-
-        // Set the new position first to be able to get the extended frame bounds
-        // WindowsApi::set_window_pos(self.hwnd(), layout, false, false)?;
-        // let mut rect = WindowsApi::window_rect(self.hwnd())?;
-
-        // Get the extended frame bounds of the new position
-        // let frame = WindowsApi::window_rect_with_extended_frame_bounds(self.hwnd())?;
-
-        // Calculate the invisible border diff
-        // let border = Rect {
-        //     left: frame.left - rect.left,
-        //     top: frame.top - rect.top,
-        //     right: rect.right - frame.right,
-        //     bottom: rect.bottom - frame.bottom,
-        // };
-
         let mut rect = *layout;
-        let border = Rect {
-            left: 12,
-            top: 0,
-            right: 24,
-            bottom: 12,
-        };
+        let border = self.border_rect()?;
 
         // Remove the invisible border
         rect.left -= border.left;
@@ -123,6 +103,52 @@ impl Window {
         WindowsApi::position_window(self.hwnd(), &rect, top)
     }
 
+    // The invisible border only depends on the window's styles and DPI, so it is calculated once
+    // per window and cached to avoid making extra DWM calls every time a layout is applied
+    pub fn border_rect(self) -> Result<Rect> {
+        let cached = BORDER_RECT_CACHE.lock().get(&self.hwnd).copied();
+        if let Some(border) = cached {
+            return Ok(border);
+        }
+
+        // The extended frame bounds of minimized windows don't tell us anything useful, so we fall
+        // back to the border that Windows 10 uses at 100% scaling without caching it
+        let fallback = Rect {
+            left: 12,
+            top: 0,
+            right: 24,
+            bottom: 12,
+        };
+
+        if WindowsApi::is_iconic(self.hwnd()) {
+            return Ok(fallback);
+        }
+
+        let rect = WindowsApi::window_rect(self.hwnd())?;
+
+        WindowsApi::window_rect_with_extended_frame_bounds(self.hwnd()).map_or(
+            Ok(fallback),
+            |frame| {
+                // Both of these Rects store the width and height in right and bottom, so the
+                // right and bottom of the border are the total horizontal and vertical sizes
+                let border = Rect {
+                    left: frame.left - rect.left,
+                    top: frame.top - rect.top,
+                    right: rect.right - frame.right,
+                    bottom: rect.bottom - frame.bottom,
+                };
+
+                BORDER_RECT_CACHE.lock().insert(self.hwnd, border);
+
+                Ok(border)
+            },
+        )
+    }
+
+    pub fn invalidate_border_rect(self) {
+        BORDER_RECT_CACHE.lock().remove(&self.hwnd);
+    }
+
     pub fn hide(self) {
         let mut programmatically_hidden_hwnds = HIDDEN_HWNDS.lock();
         if !programmatically_hidden_hwnds.contains(&self.hwnd) {
@@ -180,11 +206,105 @@ impl Window {
         WindowsApi::set_focus(self.hwnd())
     }
 
-    #[allow(dead_code)]
     pub fn update_style(self, style: GwlStyle) -> Result<()> {
+        // Any change to the frame styles also changes the size of the invisible border
+        self.invalidate_border_rect();
         WindowsApi::update_style(self.hwnd(), isize::try_from(style.bits())?)
     }
 
+    pub fn update_ex_style(self, style: GwlExStyle) -> Result<()> {
+        WindowsApi::update_ex_style(self.hwnd(), isize::try_from(style.bits())?)
+    }
+
+    pub fn set_opacity(self, opacity: u8) -> Result<()> {
+        let mut ex_style = self.ex_style()?;
+        ex_style.insert(GwlExStyle::LAYERED);
+
+        self.update_ex_style(ex_style)?;
+        WindowsApi::set_layered_window_attributes(self.hwnd(), opacity)
+    }
+
+    pub fn set_border(self, enable: bool) -> Result<()> {
+        let mut style = self.style()?;
+        style.set(GwlStyle::BORDER, enable);
+
+        self.update_style(style)?;
+
+        // Style changes to the frame are cached until SetWindowPos is called
+        WindowsApi::redraw_frame(self.hwnd())
+    }
+
+    pub fn set_border_color(self, color: Option<(u8, u8, u8)>) -> Result<()> {
+        WindowsApi::set_window_border_color(self.hwnd(), color)
+    }
+
+    pub fn toggle_border(self) -> Result<()> {
+        let mut style = self.style()?;
+        style.toggle(GwlStyle::BORDER | GwlStyle::DLGFRAME);
+
+        self.update_style(style)?;
+
+        // Style changes to the frame are cached until SetWindowPos is called
+        WindowsApi::redraw_frame(self.hwnd())
+    }
+
+    pub fn remove_title_bar(self) -> Result<()> {
+        let mut style = self.style()?;
+        // THICKFRAME is left alone so that the window can still be resized
+        style.remove(GwlStyle::SYSMENU | GwlStyle::CAPTION);
+
+        self.update_style(style)?;
+        WindowsApi::redraw_frame(self.hwnd())?;
+
+        {
+            let mut title_barless = TITLE_BARLESS_HWNDS.lock();
+            if !title_barless.contains(&self.hwnd) {
+                title_barless.push(self.hwnd);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn restore_title_bar(self) -> Result<()> {
+        let mut style = self.style()?;
+        style.insert(GwlStyle::SYSMENU | GwlStyle::CAPTION);
+
+        self.update_style(style)?;
+        WindowsApi::redraw_frame(self.hwnd())?;
+
+        TITLE_BARLESS_HWNDS.lock().retain(|h| *h != self.hwnd);
+
+        Ok(())
+    }
+
+    pub fn toggle_decorations(self) -> Result<()> {
+        let decorations = GwlStyle::CAPTION
+            | GwlStyle::SYSMENU
+            | GwlStyle::THICKFRAME
+            | GwlStyle::MINIMIZEBOX
+            | GwlStyle::MAXIMIZEBOX;
+
+        let remove = !DECORATIONLESS_HWNDS.lock().contains(&self.hwnd);
+
+        let mut style = self.style()?;
+        style.set(decorations, !remove);
+
+        self.update_style(style)?;
+        WindowsApi::redraw_frame(self.hwnd())?;
+
+        {
+            let mut decorationless = DECORATIONLESS_HWNDS.lock();
+            if remove {
+                decorationless.push(self.hwnd);
+            } else {
+                decorationless.retain(|h| *h != self.hwnd);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn style(self) -> Result<GwlStyle> {
         let bits = u32::try_from(WindowsApi::gwl_style(self.hwnd())?)?;
         GwlStyle::from_bits(bits).ok_or_else(|| anyhow!("there is no gwl style"))
@@ -204,24 +324,65 @@ impl Window {
         WindowsApi::exe(WindowsApi::process_handle(process_id)?)
     }
 
+    pub fn process_path(self) -> Result<String> {
+        let (process_id, _) = WindowsApi::window_thread_process_id(self.hwnd());
+        WindowsApi::exe_path(WindowsApi::process_handle(process_id)?)
+    }
+
     pub fn class(self) -> Result<String> {
         WindowsApi::real_window_class_w(self.hwnd())
     }
 
+    pub fn container_padding_rule(self) -> Option<i32> {
+        if CONTAINER_PADDING_RULES.lock().is_empty() {
+            return None;
+        }
+
+        let identifiers = [self.exe(), self.class(), self.title(), self.process_path()];
+        let rules = CONTAINER_PADDING_RULES.lock();
+
+        identifiers
+            .iter()
+            .flatten()
+            .find_map(|id| rules.get(id).copied())
+    }
+
     pub fn is_cloaked(self) -> Result<bool> {
         WindowsApi::is_window_cloaked(self.hwnd())
     }
 
+    pub fn is_fullscreen(self, monitor_size: &Rect) -> Result<bool> {
+        // Borderless fullscreen windows don't reliably set the maximize style, and maximized
+        // windows only cover the work area, so the only thing we can rely on is whether or not
+        // the window covers the entire monitor, including the taskbar
+        let rect = WindowsApi::window_rect(self.hwnd())?;
+
+        Ok(rect.left <= monitor_size.left
+            && rect.top <= monitor_size.top
+            && rect.left + rect.right >= monitor_size.left + monitor_size.right
+            && rect.top + rect.bottom >= monitor_size.top + monitor_size.bottom)
+    }
+
     pub fn is_window(self) -> bool {
         WindowsApi::is_window(self.hwnd())
     }
 
     #[tracing::instrument(fields(exe, title))]
-    pub fn should_manage(self, event: Option<WindowManagerEvent>) -> Result<bool> {
+    pub fn should_manage(self, event: Option<&WindowManagerEvent>) -> Result<bool> {
         if self.title().is_err() {
             return Ok(false);
         }
 
+        if let Ok(title) = self.title() {
+            if IGNORED_TITLES
+                .lock()
+                .iter()
+                .any(|regex| regex.is_match(&title))
+            {
+                return Ok(false);
+            }
+        }
+
         let is_cloaked = self.is_cloaked()?;
 
         let mut allow_cloaked = false;
@@ -234,19 +395,22 @@ impl Window {
             (true, _) |
             // If not allowing cloaked windows, we need to ensure the window is not cloaked
             (false, false) => {
-                if let (Ok(title), Ok(exe_name), Ok(class)) = (self.title(), self.exe(), self.class()) {
+                if let (Ok(title), Ok(exe_name), Ok(class), Ok(path)) = (self.title(), self.exe(), self.class(), self.process_path()) {
                     {
                         let float_identifiers = FLOAT_IDENTIFIERS.lock();
                         if float_identifiers.contains(&title)
                             || float_identifiers.contains(&exe_name)
-                            || float_identifiers.contains(&class) {
+                            || float_identifiers.contains(&class)
+                            || float_identifiers.contains(&path) {
                             return Ok(false);
                         }
                     }
 
                     let managed_override = {
                         let manage_identifiers = MANAGE_IDENTIFIERS.lock();
-                        manage_identifiers.contains(&exe_name) || manage_identifiers.contains(&class)
+                        manage_identifiers.contains(&exe_name)
+                            || manage_identifiers.contains(&class)
+                            || manage_identifiers.contains(&path)
                     };
 
                     let allow_layered = {
@@ -257,7 +421,25 @@ impl Window {
                     let style = self.style()?;
                     let ex_style = self.ex_style()?;
 
-                    if style.contains(GwlStyle::CAPTION)
+                    // Windows that we removed the title bar or decorations from should be treated
+                    // as if they still had one
+                    let has_caption = style.contains(GwlStyle::CAPTION)
+                        || TITLE_BARLESS_HWNDS.lock().contains(&self.hwnd)
+                        || DECORATIONLESS_HWNDS.lock().contains(&self.hwnd);
+
+                    // Borderless fullscreen windows (eg. games) are never meant to be tiled, even
+                    // if they have been matched by a manage rule
+                    if !has_caption
+                        && self.is_fullscreen(&WindowsApi::monitor_size_from_window(self.hwnd())?)?
+                    {
+                        if event.is_some() {
+                            tracing::debug!("ignoring borderless fullscreen window (exe: {}, title: {})", exe_name, title);
+                        }
+
+                        return Ok(false);
+                    }
+
+                    if has_caption
                         && ex_style.contains(GwlExStyle::WINDOWEDGE)
                         && !ex_style.contains(GwlExStyle::DLGMODALFRAME)
                         // Get a lot of dupe events coming through that make the redrawing go crazy
@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufReader;
 use std::io::ErrorKind;
+use std::io::Write;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -12,18 +19,24 @@ use crossbeam_channel::Receiver;
 use hotwatch::notify::DebouncedEvent;
 use hotwatch::Hotwatch;
 use parking_lot::Mutex;
+use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
 use uds_windows::UnixListener;
+use uds_windows::UnixStream;
 
 use komorebi_core::CycleDirection;
 use komorebi_core::Flip;
 use komorebi_core::Layout;
+use komorebi_core::MonitorWorkspaceMapping;
 use komorebi_core::OperationDirection;
 use komorebi_core::Rect;
 use komorebi_core::Sizing;
 
+use crate::configuration_override;
 use crate::container::Container;
 use crate::load_configuration;
+use crate::monitor;
 use crate::monitor::Monitor;
 use crate::ring::Ring;
 use crate::window::Window;
@@ -31,13 +44,26 @@ use crate::window_manager_event::WindowManagerEvent;
 use crate::windows_api::WindowsApi;
 use crate::winevent_listener::WINEVENT_CALLBACK_CHANNEL;
 use crate::workspace::Workspace;
+use crate::COLUMNS_MAIN_RATIO;
+use crate::DECORATIONLESS_HWNDS;
+use crate::DEFAULT_CONTAINER_PADDING;
+use crate::DEFAULT_WORKSPACE_PADDING;
 use crate::FLOAT_IDENTIFIERS;
+use crate::FOCUS_BORDER_COLOR;
 use crate::LAYERED_EXE_WHITELIST;
 use crate::MANAGE_IDENTIFIERS;
+use crate::MINIMUM_CONTAINER_SIZE;
+use crate::MONITOR_INDEX_PREFERENCES;
+use crate::PINNED_TO_WORKSPACE;
+use crate::SUBSCRIPTION_SOCKETS;
+use crate::TITLE_BARLESS_HWNDS;
 use crate::TRAY_AND_MULTI_WINDOW_CLASSES;
 use crate::TRAY_AND_MULTI_WINDOW_EXES;
+use crate::UNFOCUS_BORDER_COLOR;
+use crate::WINDOW_LOCATION_CACHE;
 use crate::WORKSPACE_RULES;
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug)]
 pub struct WindowManager {
     pub monitors: Ring<Monitor>,
@@ -46,6 +72,18 @@ pub struct WindowManager {
     pub is_paused: bool,
     pub hotwatch: Hotwatch,
     pub virtual_desktop_id: Option<usize>,
+    pub virtual_desktop_integration: bool,
+    pub dynamic_workspace_naming: bool,
+    pub auto_fullscreen_pause: bool,
+    pub monocle_include_floating: bool,
+    pub focus_border: bool,
+    pub focus_border_hwnd: Option<isize>,
+    pub hide_title_bars: bool,
+    pub fullscreen_hwnd: Option<isize>,
+    pub fullscreen_monitor_idx: Option<usize>,
+    pub paused_before_fullscreen: bool,
+    pub scratchpads: HashMap<String, Vec<Container>>,
+    pub visible_scratchpads: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -57,6 +95,64 @@ pub struct State {
     pub layered_exe_whitelist: Vec<String>,
     pub tray_and_multi_window_exes: Vec<String>,
     pub tray_and_multi_window_classes: Vec<String>,
+    pub focus_border_color: Option<(u8, u8, u8)>,
+    pub unfocus_border_color: Option<(u8, u8, u8)>,
+}
+
+// Written to komorebi.state.json by `komorebic stop --save-state`, with one entry for each workspace
+// on each monitor, so that the arrangement of windows can be restored after komorebi is restarted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedWorkspace {
+    pub name: Option<String>,
+    pub layout: Layout,
+    pub layout_flip: Option<Flip>,
+    pub containers: Vec<Vec<isize>>,
+    pub floating_windows: Vec<isize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowLocation {
+    pub monitor_idx: usize,
+    pub workspace_idx: usize,
+    pub container_idx: usize,
+    pub window_idx: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManagedStatus {
+    pub managed: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub floating: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonitorInformation {
+    pub id: isize,
+    pub device_name: String,
+    pub work_area: Rect,
+    pub monitor_area: Rect,
+    pub dpi: u32,
+    pub is_primary: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowProcessId {
+    pub pid: u32,
+    pub tid: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FocusedWorkspaceIndex {
+    pub monitor_idx: usize,
+    pub workspace_idx: usize,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceWindowCount {
+    pub containers: usize,
+    pub floating: usize,
+    pub name: Option<String>,
 }
 
 #[allow(clippy::fallible_impl_from)]
@@ -70,6 +166,8 @@ impl From<&mut WindowManager> for State {
             layered_exe_whitelist: LAYERED_EXE_WHITELIST.lock().clone(),
             tray_and_multi_window_exes: TRAY_AND_MULTI_WINDOW_EXES.lock().clone(),
             tray_and_multi_window_classes: TRAY_AND_MULTI_WINDOW_CLASSES.lock().clone(),
+            focus_border_color: *FOCUS_BORDER_COLOR.lock(),
+            unfocus_border_color: *UNFOCUS_BORDER_COLOR.lock(),
         }
     }
 }
@@ -79,33 +177,36 @@ impl_ring_elements!(WindowManager, Monitor);
 #[derive(Debug, Clone, Copy)]
 struct EnforceWorkspaceRuleOp {
     hwnd: isize,
-    origin_monitor_idx: usize,
-    origin_workspace_idx: usize,
-    target_monitor_idx: usize,
-    target_workspace_idx: usize,
+    origin: MonitorWorkspaceMapping,
+    target: MonitorWorkspaceMapping,
 }
 
 impl EnforceWorkspaceRuleOp {
     const fn is_origin(&self, monitor_idx: usize, workspace_idx: usize) -> bool {
-        self.origin_monitor_idx == monitor_idx && self.origin_workspace_idx == workspace_idx
+        self.origin.monitor_idx == monitor_idx && self.origin.workspace_idx == workspace_idx
     }
 
     const fn is_target(&self, monitor_idx: usize, workspace_idx: usize) -> bool {
-        self.target_monitor_idx == monitor_idx && self.target_workspace_idx == workspace_idx
+        self.target.monitor_idx == monitor_idx && self.target.workspace_idx == workspace_idx
     }
 
-    const fn is_enforced(&self) -> bool {
-        (self.origin_monitor_idx == self.target_monitor_idx)
-            && (self.origin_workspace_idx == self.target_workspace_idx)
+    fn is_enforced(&self) -> bool {
+        self.origin == self.target
     }
 }
 
 impl WindowManager {
     #[tracing::instrument]
     pub fn new(incoming: Arc<Mutex<Receiver<WindowManagerEvent>>>) -> Result<Self> {
-        let home = dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
-        let mut socket = home;
-        socket.push("komorebi.sock");
+        let socket = if let Ok(path) = std::env::var("KOMOREBI_SOCKET_PATH") {
+            PathBuf::from(path)
+        } else {
+            let mut socket =
+                dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
+            socket.push("komorebi.sock");
+            socket
+        };
+
         let socket = socket.as_path();
 
         match std::fs::remove_file(&socket) {
@@ -130,9 +231,50 @@ impl WindowManager {
             is_paused: false,
             hotwatch: Hotwatch::new()?,
             virtual_desktop_id,
+            virtual_desktop_integration: false,
+            dynamic_workspace_naming: false,
+            auto_fullscreen_pause: false,
+            monocle_include_floating: false,
+            focus_border: false,
+            focus_border_hwnd: None,
+            hide_title_bars: false,
+            fullscreen_hwnd: None,
+            fullscreen_monitor_idx: None,
+            paused_before_fullscreen: false,
+            scratchpads: HashMap::new(),
+            visible_scratchpads: vec![],
         })
     }
 
+    // Every subscriber gets the full state after each event or command so that they never have
+    // to poll for it; subscribers that can no longer be connected to are dropped
+    pub fn notify_subscribers(&mut self) -> Result<()> {
+        let subscribers = SUBSCRIPTION_SOCKETS.lock().clone();
+        if subscribers.is_empty() {
+            return Ok(());
+        }
+
+        let state = serde_json::to_string(&State::from(&mut *self))?;
+
+        let mut disconnected = vec![];
+        for socket in subscribers {
+            if let Ok(mut stream) = UnixStream::connect(&socket) {
+                if let Err(error) = writeln!(stream, "{}", state) {
+                    tracing::warn!("could not notify subscriber {}: {}", socket, error);
+                }
+            } else {
+                tracing::info!("removing disconnected subscriber: {}", socket);
+                disconnected.push(socket);
+            }
+        }
+
+        SUBSCRIPTION_SOCKETS
+            .lock()
+            .retain(|socket| !disconnected.contains(socket));
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn init(&mut self) -> Result<()> {
         tracing::info!("initialising");
@@ -149,6 +291,10 @@ impl WindowManager {
 
     #[tracing::instrument(skip(self))]
     pub fn watch_configuration(&mut self, enable: bool) -> Result<()> {
+        if let Some(config) = configuration_override() {
+            return self.configure_watcher(enable, config);
+        }
+
         let home = dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
 
         let mut config_v1 = home.clone();
@@ -226,46 +372,55 @@ impl WindowManager {
             .ok_or_else(|| anyhow!("there is no monitor with that index"))?
             .focused_workspace_idx();
 
+        let monitor_count = self.monitors.len();
+        let pinned_to_workspace = PINNED_TO_WORKSPACE.lock().clone();
         let workspace_rules = WORKSPACE_RULES.lock();
+        let rule_for = |id: &String| {
+            workspace_rules
+                .get(id)
+                .filter(|(_, condition)| condition.is_met(monitor_count))
+        };
+
         // Go through all the monitors and workspaces
-        for (i, monitor) in self.monitors().iter().enumerate() {
+        for (i, monitor) in self.monitors.iter().enumerate() {
             for (j, workspace) in monitor.workspaces().iter().enumerate() {
                 // And all the visible windows (at the top of a container)
                 for window in workspace.visible_windows().into_iter().flatten() {
-                    // If the executable names or titles of any of those windows are in our rules map
-                    if let Some((monitor_idx, workspace_idx)) = workspace_rules.get(&window.exe()?)
+                    // Windows that have been pinned stay wherever they were pinned
+                    if pinned_to_workspace.contains_key(&window.hwnd) {
+                        continue;
+                    }
+
+                    // If the executable names, paths or titles of any of those windows are in our
+                    // rules map
+                    if let Some((target, _)) =
+                        rule_for(&window.exe()?).or_else(|| rule_for(&window.process_path().ok()?))
                     {
                         tracing::info!(
                             "{} should be on monitor {}, workspace {}",
                             window.title()?,
-                            *monitor_idx,
-                            *workspace_idx
+                            target.monitor_idx,
+                            target.workspace_idx
                         );
 
                         // Create an operation outline and save it for later in the fn
                         to_move.push(EnforceWorkspaceRuleOp {
                             hwnd: window.hwnd,
-                            origin_monitor_idx: i,
-                            origin_workspace_idx: j,
-                            target_monitor_idx: *monitor_idx,
-                            target_workspace_idx: *workspace_idx,
+                            origin: MonitorWorkspaceMapping::new(i, j),
+                            target: *target,
                         });
-                    } else if let Some((monitor_idx, workspace_idx)) =
-                        workspace_rules.get(&window.title()?)
-                    {
+                    } else if let Some((target, _)) = rule_for(&window.title()?) {
                         tracing::info!(
                             "{} should be on monitor {}, workspace {}",
                             window.title()?,
-                            *monitor_idx,
-                            *workspace_idx
+                            target.monitor_idx,
+                            target.workspace_idx
                         );
 
                         to_move.push(EnforceWorkspaceRuleOp {
                             hwnd: window.hwnd,
-                            origin_monitor_idx: i,
-                            origin_workspace_idx: j,
-                            target_monitor_idx: *monitor_idx,
-                            target_workspace_idx: *workspace_idx,
+                            origin: MonitorWorkspaceMapping::new(i, j),
+                            target: *target,
                         });
                     }
                 }
@@ -281,13 +436,9 @@ impl WindowManager {
 
         // Parse the operation and remove any windows that are not placed according to their rules
         for op in &to_move {
-            let origin_workspace = self
-                .monitors_mut()
-                .get_mut(op.origin_monitor_idx)
-                .ok_or_else(|| anyhow!("there is no monitor with that index"))?
-                .workspaces_mut()
-                .get_mut(op.origin_workspace_idx)
-                .ok_or_else(|| anyhow!("there is no workspace with that index"))?;
+            // The origin indices were taken from the loop above, so they are always valid
+            let origin_workspace =
+                &mut self.monitors[op.origin.monitor_idx].workspaces_mut()[op.origin.workspace_idx];
 
             // Hide the window we are about to remove if it is on the currently focused workspace
             if op.is_origin(focused_monitor_idx, focused_workspace_idx) {
@@ -303,22 +454,22 @@ impl WindowManager {
         for op in &to_move {
             let target_monitor = self
                 .monitors_mut()
-                .get_mut(op.target_monitor_idx)
+                .get_mut(op.target.monitor_idx)
                 .ok_or_else(|| anyhow!("there is no monitor with that index"))?;
 
             // The very first time this fn is called, the workspace might not even exist yet
             if target_monitor
                 .workspaces()
-                .get(op.target_workspace_idx)
+                .get(op.target.workspace_idx)
                 .is_none()
             {
                 // If it doesn't, let's make sure it does for the next step
-                target_monitor.ensure_workspace_count(op.target_workspace_idx + 1);
+                target_monitor.ensure_workspace_count(op.target.workspace_idx + 1)?;
             }
 
             let target_workspace = target_monitor
                 .workspaces_mut()
-                .get_mut(op.target_workspace_idx)
+                .get_mut(op.target.workspace_idx)
                 .ok_or_else(|| anyhow!("there is no workspace with that index"))?;
 
             target_workspace.new_container_for_window(Window { hwnd: op.hwnd });
@@ -346,6 +497,36 @@ impl WindowManager {
         }
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn set_virtual_desktop_integration(&mut self, enable: bool) {
+        tracing::info!("setting virtual desktop integration");
+
+        self.virtual_desktop_integration = enable;
+    }
+
+    // Each workspace index is bound to the virtual desktop with the same number
+    fn ensure_virtual_desktop_for_workspace(idx: usize) -> Result<()> {
+        let count = winvd::helpers::get_desktop_count()
+            .map_err(|error| anyhow!("could not get virtual desktop count: {:?}", error))?;
+
+        if idx >= count {
+            return Err(anyhow!("there is no virtual desktop for workspace {}", idx));
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn go_to_virtual_desktop(&mut self, idx: usize) -> Result<()> {
+        winvd::helpers::go_to_desktop_number(idx)
+            .map_err(|error| anyhow!("could not go to virtual desktop {}: {:?}", idx, error))?;
+
+        // Events are only processed for the virtual desktop that komorebi considers its own
+        self.virtual_desktop_id = Option::from(idx);
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn manage_focused_window(&mut self) -> Result<()> {
         let hwnd = WindowsApi::foreground_window()?;
@@ -353,6 +534,161 @@ impl WindowManager {
         Ok(WINEVENT_CALLBACK_CHANNEL.lock().0.send(event)?)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_window_border(&mut self) -> Result<()> {
+        tracing::info!("toggling window border");
+
+        let hwnd = WindowsApi::foreground_window()?;
+        Window { hwnd }.toggle_border()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_window_decoration(&mut self) -> Result<()> {
+        tracing::info!("toggling window decoration");
+
+        let hwnd = WindowsApi::foreground_window()?;
+        Window { hwnd }.toggle_decorations()?;
+
+        // The client area of the window will have changed size, so it needs to be repositioned
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_focus_border(&mut self) -> Result<()> {
+        tracing::info!("toggling focus border");
+
+        self.focus_border = !self.focus_border;
+        self.update_focus_border()?;
+        self.repaint_border_colors()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_focus_border_color(&self, r: u8, g: u8, b: u8) -> Result<()> {
+        tracing::info!("setting focus border color");
+
+        *FOCUS_BORDER_COLOR.lock() = Option::from((r, g, b));
+        self.repaint_border_colors()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_unfocus_border_color(&self, r: u8, g: u8, b: u8) -> Result<()> {
+        tracing::info!("setting unfocus border color");
+
+        *UNFOCUS_BORDER_COLOR.lock() = Option::from((r, g, b));
+        self.repaint_border_colors()
+    }
+
+    // Border colors are left alone until one has been set, as setting them fails on versions of
+    // Windows older than Windows 11
+    fn border_colors_enabled() -> bool {
+        FOCUS_BORDER_COLOR.lock().is_some() || UNFOCUS_BORDER_COLOR.lock().is_some()
+    }
+
+    fn border_color(&self, hwnd: isize) -> Option<(u8, u8, u8)> {
+        if !self.focus_border {
+            None
+        } else if self.focus_border_hwnd == Option::from(hwnd) {
+            *FOCUS_BORDER_COLOR.lock()
+        } else {
+            *UNFOCUS_BORDER_COLOR.lock()
+        }
+    }
+
+    pub fn repaint_border_colors(&self) -> Result<()> {
+        if !Self::border_colors_enabled() {
+            return Ok(());
+        }
+
+        // Windows on other workspaces are included so that they have the right color when shown
+        for monitor in self.monitors() {
+            for workspace in monitor.workspaces() {
+                for container in workspace.containers() {
+                    for window in container.windows() {
+                        window.set_border_color(self.border_color(window.hwnd))?;
+                    }
+                }
+
+                for window in workspace.floating_windows() {
+                    window.set_border_color(self.border_color(window.hwnd))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn update_focus_border(&mut self) -> Result<()> {
+        let focused_hwnd = if self.focus_border {
+            self.focused_container()
+                .ok()
+                .and_then(Container::focused_window)
+                .map(|window| window.hwnd)
+        } else {
+            None
+        };
+
+        if focused_hwnd == self.focus_border_hwnd {
+            return Ok(());
+        }
+
+        if let Some(hwnd) = self.focus_border_hwnd.take() {
+            // The window that previously had the border may have been closed in the meantime
+            if WindowsApi::is_window(Window { hwnd }.hwnd()) {
+                Window { hwnd }.set_border(false)?;
+
+                if Self::border_colors_enabled() {
+                    Window { hwnd }.set_border_color(self.border_color(hwnd))?;
+                }
+            }
+        }
+
+        if let Some(hwnd) = focused_hwnd {
+            Window { hwnd }.set_border(true)?;
+            self.focus_border_hwnd = Option::from(hwnd);
+
+            if Self::border_colors_enabled() {
+                Window { hwnd }.set_border_color(self.border_color(hwnd))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_title_bars(&mut self) -> Result<()> {
+        tracing::info!("toggling title bars");
+
+        self.hide_title_bars = !self.hide_title_bars;
+
+        if self.hide_title_bars {
+            self.update_title_bars()
+        } else {
+            restore_title_bars()
+        }
+    }
+
+    pub fn update_title_bars(&self) -> Result<()> {
+        let title_barless = TITLE_BARLESS_HWNDS.lock().clone();
+        let decorationless = DECORATIONLESS_HWNDS.lock().clone();
+
+        for monitor in self.monitors() {
+            for workspace in monitor.workspaces() {
+                for container in workspace.containers() {
+                    for window in container.windows() {
+                        // Windows without any decorations don't have a title bar to remove
+                        if !title_barless.contains(&window.hwnd)
+                            && !decorationless.contains(&window.hwnd)
+                        {
+                            window.remove_title_bar()?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn unmanage_focused_window(&mut self) -> Result<()> {
         let hwnd = WindowsApi::foreground_window()?;
@@ -360,6 +696,41 @@ impl WindowManager {
         Ok(WINEVENT_CALLBACK_CHANNEL.lock().0.send(event)?)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn unmanage_window(&mut self, hwnd: isize) -> Result<()> {
+        tracing::info!("unmanaging window");
+
+        let (monitor_idx, workspace_idx) = self
+            .workspace_for_window(hwnd)
+            .ok_or_else(|| anyhow!("there is no managed window with this hwnd"))?;
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let work_area = monitor.work_area_with_offset();
+        let scale_factor = monitor.padding_scale_factor();
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        workspace.remove_window(hwnd)?;
+
+        // Retile straight away if the window was on a visible workspace
+        if workspace_idx == focused_workspace_idx {
+            workspace.update(&work_area, scale_factor)?;
+        }
+
+        // The window may have been hidden on an unfocused workspace or in a container stack
+        Window { hwnd }.restore();
+
+        self.update_focused_workspace(false)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn update_focused_workspace(&mut self, mouse_follows_focus: bool) -> Result<()> {
         tracing::info!("updating");
@@ -368,6 +739,16 @@ impl WindowManager {
             .ok_or_else(|| anyhow!("there is no monitor"))?
             .update_focused_workspace()?;
 
+        if self.dynamic_workspace_naming {
+            self.update_dynamic_workspace_name()?;
+        }
+
+        // A workspace-specific setting always takes priority over the caller's preference
+        let mouse_follows_focus = self
+            .focused_workspace()?
+            .mouse_follows_focus()
+            .unwrap_or(mouse_follows_focus);
+
         if mouse_follows_focus {
             if let Some(window) = self.focused_workspace()?.maximized_window() {
                 window.focus()?;
@@ -393,6 +774,57 @@ impl WindowManager {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self))]
+    fn update_dynamic_workspace_name(&mut self) -> Result<()> {
+        let monitor = self
+            .focused_monitor_mut()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        // Names that have been set manually always take precedence
+        if monitor
+            .workspace_names()
+            .contains_key(&monitor.focused_workspace_idx())
+        {
+            return Ok(());
+        }
+
+        let workspace = monitor
+            .focused_workspace_mut()
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        let name = workspace
+            .focused_container()
+            .and_then(Container::focused_window)
+            .and_then(|window| window.exe().ok());
+
+        workspace.set_name(name);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_dynamic_workspace_naming(&mut self, enable: bool) -> Result<()> {
+        tracing::info!("setting dynamic workspace naming");
+
+        self.dynamic_workspace_naming = enable;
+
+        if enable {
+            return self.update_dynamic_workspace_name();
+        }
+
+        // Clear out any names that were set dynamically
+        for monitor in self.monitors_mut() {
+            let workspace_names = monitor.workspace_names().clone();
+            for (i, workspace) in monitor.workspaces_mut().iter_mut().enumerate() {
+                if !workspace_names.contains_key(&i) {
+                    workspace.set_name(None);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn resize_window(
         &mut self,
@@ -406,8 +838,8 @@ impl WindowManager {
         let workspace = self.focused_workspace_mut()?;
         let len = workspace.containers().len();
         let focused_idx = workspace.focused_container_idx();
-        let focused_idx_resize = workspace
-            .resize_dimensions()
+        let focused_idx_resize = *workspace
+            .resize_dimensions_mut()
             .get(focused_idx)
             .ok_or_else(|| anyhow!("there is no resize adjustment for this container"))?;
 
@@ -423,9 +855,10 @@ impl WindowManager {
                     "there must be at least one container to calculate a workspace layout",
                 )?,
                 workspace.container_padding(),
+                workspace.gap().unwrap_or_default(),
                 workspace.layout_flip(),
                 &[],
-            );
+            )?;
 
             let mut direction = direction;
 
@@ -455,12 +888,29 @@ impl WindowManager {
                 unaltered
                     .get(focused_idx)
                     .ok_or_else(|| anyhow!("there is no last layout"))?,
-                focused_idx_resize,
+                &focused_idx_resize,
                 direction,
                 sizing,
                 step,
             );
 
+            let resize_dimensions = workspace.resize_dimensions_mut().clone();
+            let resize = clamp_resize_to_minimum_size(focused_idx_resize, resize, |resize| {
+                let mut resize_dimensions = resize_dimensions.clone();
+                resize_dimensions[focused_idx] = resize;
+
+                workspace.layout().calculate(
+                    &work_area,
+                    NonZeroUsize::new(len).context(
+                        "there must be at least one container to calculate a workspace layout",
+                    )?,
+                    workspace.container_padding(),
+                    workspace.gap().unwrap_or_default(),
+                    workspace.layout_flip(),
+                    &resize_dimensions,
+                )
+            })?;
+
             workspace.resize_dimensions_mut()[focused_idx] = resize;
             self.update_focused_workspace(false)
         } else {
@@ -470,52 +920,461 @@ impl WindowManager {
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn restore_all_windows(&mut self) {
-        tracing::info!("restoring all hidden windows");
+    pub fn resize_window_absolute(
+        &mut self,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Result<()> {
+        tracing::info!("resizing window to absolute dimensions");
 
-        for monitor in self.monitors_mut() {
-            for workspace in monitor.workspaces_mut() {
-                for containers in workspace.containers_mut() {
-                    for window in containers.windows_mut() {
-                        window.restore();
-                    }
-                }
-            }
+        let work_area = self.focused_monitor_work_area()?;
+        let workspace = self.focused_workspace_mut()?;
+
+        if !matches!(workspace.layout(), Layout::BSP) {
+            tracing::warn!("containers can only be resized on the bsp layout");
+            return Ok(());
         }
-    }
 
-    #[tracing::instrument(skip(self))]
-    pub fn move_container_to_monitor(&mut self, idx: usize, follow: bool) -> Result<()> {
-        tracing::info!("moving container");
+        let len = workspace.containers().len();
+        let focused_idx = workspace.focused_container_idx();
+        let mut resize_dimensions = workspace.resize_dimensions_mut().clone();
+        let current = resize_dimensions
+            .get(focused_idx)
+            .copied()
+            .ok_or_else(|| anyhow!("there is no resize adjustment for this container"))?;
 
-        let monitor = self
-            .focused_monitor_mut()
-            .ok_or_else(|| anyhow!("there is no monitor"))?;
-        let workspace = monitor
-            .focused_workspace_mut()
-            .ok_or_else(|| anyhow!("there is no workspace"))?;
+        let calculate = |resize_dimensions: &[Option<Rect>]| {
+            workspace.layout().calculate(
+                &work_area,
+                NonZeroUsize::new(len).context(
+                    "there must be at least one container to calculate a workspace layout",
+                )?,
+                workspace.container_padding(),
+                workspace.gap().unwrap_or_default(),
+                workspace.layout_flip(),
+                resize_dimensions,
+            )
+        };
 
-        if workspace.maximized_window().is_some() {
-            return Err(anyhow!(
-                "cannot move native maximized window to another monitor or workspace"
-            ));
-        }
+        let axes = [
+            (width, [OperationDirection::Right, OperationDirection::Left]),
+            (height, [OperationDirection::Down, OperationDirection::Up]),
+        ];
+
+        let targets = axes
+            .iter()
+            .filter_map(|(target, edges)| target.map(|target| (target, edges)));
+
+        for (target, edges) in targets {
+            let edge = edges
+                .iter()
+                .find(|edge| {
+                    edge.is_valid(
+                        workspace.layout(),
+                        workspace.layout_flip(),
+                        focused_idx,
+                        len,
+                    )
+                })
+                .map(|edge| OperationDirection::flip_direction(*edge, workspace.layout_flip()));
+
+            if let Some(edge) = edge {
+                let set_edge = |resize: &mut Rect, value: i32| match edge {
+                    OperationDirection::Left => resize.left = value,
+                    OperationDirection::Right => resize.right = value,
+                    OperationDirection::Up => resize.top = value,
+                    OperationDirection::Down => resize.bottom = value,
+                };
 
-        let container = workspace
-            .remove_focused_container()
-            .ok_or_else(|| anyhow!("there is no container"))?;
+                let dimension = |rect: &Rect| match edge {
+                    OperationDirection::Left | OperationDirection::Right => rect.right,
+                    OperationDirection::Up | OperationDirection::Down => rect.bottom,
+                };
 
-        let target_monitor = self
-            .monitors_mut()
-            .get_mut(idx)
-            .ok_or_else(|| anyhow!("there is no monitor"))?;
+                // Depending on where the container sits in the tree, moving an edge by n pixels
+                // does not necessarily change the size of the container by n pixels, so we compare
+                // the unaltered layout rect with one resized by a known amount to find the delta
+                let probe = 100;
+                let mut resize = resize_dimensions[focused_idx].unwrap_or_default();
+
+                set_edge(&mut resize, 0);
+                resize_dimensions[focused_idx] = Option::from(resize);
+                let unaltered = dimension(
+                    calculate(&resize_dimensions)?
+                        .get(focused_idx)
+                        .ok_or_else(|| anyhow!("there is no last layout"))?,
+                );
 
-        target_monitor.add_container(container)?;
-        target_monitor.load_focused_workspace()?;
+                set_edge(&mut resize, probe);
+                resize_dimensions[focused_idx] = Option::from(resize);
+                let probed = dimension(
+                    calculate(&resize_dimensions)?
+                        .get(focused_idx)
+                        .ok_or_else(|| anyhow!("there is no last layout"))?,
+                );
 
-        if follow {
-            self.focus_monitor(idx)?;
-        }
+                let change_per_probe = probed - unaltered;
+                if change_per_probe == 0 {
+                    tracing::warn!("cannot resize container on this axis");
+                    set_edge(&mut resize, 0);
+                } else {
+                    set_edge(&mut resize, (target - unaltered) * probe / change_per_probe);
+                }
+
+                resize_dimensions[focused_idx] = if resize.eq(&Rect::default()) {
+                    None
+                } else {
+                    Option::from(resize)
+                };
+            } else {
+                tracing::warn!("cannot resize container on this axis");
+            }
+        }
+
+        let resize =
+            clamp_resize_to_minimum_size(current, resize_dimensions[focused_idx], |resize| {
+                let mut resize_dimensions = resize_dimensions.clone();
+                resize_dimensions[focused_idx] = resize;
+                calculate(&resize_dimensions)
+            })?;
+
+        workspace.resize_dimensions_mut()[focused_idx] = resize;
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn save_state(&self) -> Result<()> {
+        tracing::info!("saving state");
+
+        let mut saved = vec![];
+        for monitor in self.monitors() {
+            let mut workspaces = vec![];
+            for workspace in monitor.workspaces() {
+                let mut containers: Vec<Vec<isize>> = workspace
+                    .containers()
+                    .iter()
+                    .map(|container| container.windows().iter().map(|w| w.hwnd).collect())
+                    .collect();
+
+                if let Some(container) = workspace.monocle_container() {
+                    containers.push(container.windows().iter().map(|w| w.hwnd).collect());
+                }
+
+                if let Some(window) = workspace.maximized_window() {
+                    containers.push(vec![window.hwnd]);
+                }
+
+                workspaces.push(SavedWorkspace {
+                    name: workspace.name().clone(),
+                    layout: workspace.layout().clone(),
+                    layout_flip: workspace.layout_flip(),
+                    containers,
+                    floating_windows: workspace
+                        .floating_windows()
+                        .iter()
+                        .map(|w| w.hwnd)
+                        .collect(),
+                });
+            }
+
+            saved.push(workspaces);
+        }
+
+        let file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(saved_state_path()?)?;
+
+        serde_json::to_writer_pretty(&file, &saved)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn restore_state(&mut self) -> Result<()> {
+        tracing::info!("restoring saved state");
+
+        let path = saved_state_path()?;
+        let mut saved: Vec<Vec<SavedWorkspace>> =
+            serde_json::from_reader(BufReader::new(File::open(&path)?))?;
+
+        // Anything saved for monitors that are no longer connected is left where it is now
+        saved.truncate(self.monitors().len());
+
+        for (monitor_idx, workspaces) in saved.iter().enumerate() {
+            self.monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .ensure_workspace_count(workspaces.len())?;
+        }
+
+        // Only windows that are currently in a container or floating are moved, so anything that has
+        // been closed, stopped being managed or is in monocle or maximized is left where it is. All of
+        // the windows are looked up before any of them are removed, so that a failure can't leave the
+        // workspaces half-restored
+        let mut restored = HashSet::new();
+        let mut removals = vec![];
+        for workspace in saved.iter().flatten() {
+            for &hwnd in workspace
+                .containers
+                .iter()
+                .flatten()
+                .chain(workspace.floating_windows.iter())
+            {
+                if let Some((monitor_idx, workspace_idx)) = self.workspace_for_window(hwnd) {
+                    let movable = matches!(
+                        self.workspace_by_idx(monitor_idx, workspace_idx),
+                        Some(current) if current.can_remove_window(hwnd)
+                    );
+
+                    if movable && restored.insert(hwnd) {
+                        removals.push((monitor_idx, workspace_idx, hwnd));
+                    }
+                }
+            }
+        }
+
+        for (monitor_idx, workspace_idx, hwnd) in removals {
+            self.monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?
+                .workspaces_mut()
+                .get_mut(workspace_idx)
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .remove_window(hwnd)?;
+        }
+
+        WINDOW_LOCATION_CACHE.lock().clear();
+
+        for (monitor_idx, workspaces) in saved.into_iter().enumerate() {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+            for (workspace_idx, saved_workspace) in workspaces.into_iter().enumerate() {
+                let workspace = monitor
+                    .workspaces_mut()
+                    .get_mut(workspace_idx)
+                    .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+                if saved_workspace.name.is_some() {
+                    workspace.set_name(saved_workspace.name);
+                }
+
+                workspace.set_layout(saved_workspace.layout);
+                workspace.set_layout_flip(saved_workspace.layout_flip);
+
+                for hwnds in saved_workspace.containers {
+                    let mut container = Container::default();
+                    for hwnd in hwnds.into_iter().filter(|hwnd| restored.contains(hwnd)) {
+                        container.add_window(Window { hwnd });
+                    }
+
+                    if !container.windows().is_empty() {
+                        workspace.add_container(container);
+                    }
+                }
+
+                for hwnd in saved_workspace.floating_windows {
+                    if restored.contains(&hwnd) {
+                        workspace.add_floating_window(Window { hwnd });
+                    }
+                }
+            }
+
+            monitor.load_focused_workspace()?;
+        }
+
+        std::fs::remove_file(path)?;
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn restore_all_windows(&mut self) {
+        tracing::info!("restoring all hidden windows");
+
+        for monitor in &mut self.monitors {
+            for workspace in monitor.workspaces_mut() {
+                for containers in workspace.containers_mut() {
+                    for window in containers.windows_mut() {
+                        window.restore();
+                    }
+                }
+            }
+        }
+
+        for containers in self.scratchpads.values_mut() {
+            for container in containers {
+                for window in container.windows_mut() {
+                    window.restore();
+                }
+            }
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn restore_window_styles(&mut self) {
+        tracing::info!("removing the focus border and restoring window styles");
+
+        if let Some(hwnd) = self.focus_border_hwnd.take() {
+            if let Err(error) = (Window { hwnd }).set_border(false) {
+                tracing::error!("could not remove focus border: {}", error);
+            }
+        }
+
+        self.focus_border = false;
+        if let Err(error) = self.repaint_border_colors() {
+            tracing::error!("could not reset border colors: {}", error);
+        }
+
+        if let Err(error) = restore_title_bars() {
+            tracing::error!("could not restore title bars: {}", error);
+        }
+
+        if let Err(error) = restore_window_decorations() {
+            tracing::error!("could not restore window decorations: {}", error);
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn handle_fullscreen_pause(&mut self, event: &WindowManagerEvent) -> Result<()> {
+        match event {
+            WindowManagerEvent::Show(_, window) if self.fullscreen_hwnd.is_none() => {
+                let monitor_idx = match self.monitor_idx_from_window(*window) {
+                    None => return Ok(()),
+                    Some(idx) => idx,
+                };
+
+                let monitor = self
+                    .monitors_mut()
+                    .get_mut(monitor_idx)
+                    .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+                let is_fullscreen = match window.is_fullscreen(monitor.monitor_size()) {
+                    Ok(is_fullscreen) => is_fullscreen,
+                    Err(error) => {
+                        tracing::error!("could not check if the window is fullscreen: {}", error);
+                        return Ok(());
+                    }
+                };
+
+                if is_fullscreen {
+                    tracing::info!("pausing monitor for fullscreen window");
+                    let paused_before_fullscreen = monitor.paused();
+                    monitor.set_paused(true);
+
+                    // Only the monitor with the fullscreen window is paused, so the hidden
+                    // windows of the other monitors are left alone
+                    for workspace in monitor.workspaces_mut() {
+                        for container in workspace.containers_mut() {
+                            for hidden in container.windows_mut() {
+                                hidden.restore();
+                            }
+                        }
+                    }
+
+                    self.fullscreen_hwnd = Option::from(window.hwnd);
+                    self.fullscreen_monitor_idx = Option::from(monitor_idx);
+                    self.paused_before_fullscreen = paused_before_fullscreen;
+                }
+            }
+            WindowManagerEvent::Hide(_, window)
+            | WindowManagerEvent::Minimize(_, window)
+            | WindowManagerEvent::Destroy(_, window)
+                if self.fullscreen_hwnd == Option::from(window.hwnd) =>
+            {
+                self.end_fullscreen_pause()?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn end_fullscreen_pause(&mut self) -> Result<()> {
+        tracing::info!("resuming after fullscreen window");
+
+        self.fullscreen_hwnd = None;
+        let monitor_idx = self
+            .fullscreen_monitor_idx
+            .take()
+            .ok_or_else(|| anyhow!("there is no fullscreen monitor"))?;
+
+        let paused_before_fullscreen = self.paused_before_fullscreen;
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        // If the monitor had already been paused by the user, it stays paused
+        monitor.set_paused(paused_before_fullscreen);
+
+        // Hide the windows of any workspaces that were restored when we paused, and then retile
+        // everything that is supposed to be visible
+        if !paused_before_fullscreen {
+            monitor.load_focused_workspace()?;
+            monitor.update_focused_workspace()?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_auto_fullscreen_pause(&mut self, enable: bool) -> Result<()> {
+        tracing::info!("setting auto fullscreen pause");
+
+        self.auto_fullscreen_pause = enable;
+
+        if !enable && self.fullscreen_hwnd.is_some() {
+            self.end_fullscreen_pause()?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn move_container_to_monitor(&mut self, idx: usize, follow: bool) -> Result<()> {
+        tracing::info!("moving container");
+
+        WINDOW_LOCATION_CACHE.lock().clear();
+
+        let monitor = self
+            .focused_monitor_mut()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+        let workspace = monitor
+            .focused_workspace_mut()
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        if workspace.maximized_window().is_some() {
+            return Err(anyhow!(
+                "cannot move native maximized window to another monitor or workspace"
+            ));
+        }
+
+        let container = workspace
+            .remove_focused_container()
+            .ok_or_else(|| anyhow!("there is no container"))?;
+
+        // The invisible border is scaled with the DPI of the monitor that the window is on
+        for window in container.windows() {
+            window.invalidate_border_rect();
+        }
+
+        let target_monitor = self
+            .monitors_mut()
+            .get_mut(idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        target_monitor.add_container(container)?;
+        target_monitor.load_focused_workspace()?;
+
+        if follow {
+            self.focus_monitor(idx)?;
+        }
 
         self.update_focused_workspace(true)
     }
@@ -524,14 +1383,184 @@ impl WindowManager {
     pub fn move_container_to_workspace(&mut self, idx: usize, follow: bool) -> Result<()> {
         tracing::info!("moving container");
 
+        WINDOW_LOCATION_CACHE.lock().clear();
+
+        if self.virtual_desktop_integration {
+            Self::ensure_virtual_desktop_for_workspace(idx)?;
+
+            for window in self.focused_container()?.windows() {
+                winvd::helpers::move_window_to_desktop_number(
+                    winvd::HWND::try_from(window.hwnd)?,
+                    idx,
+                )
+                .map_err(|error| {
+                    anyhow!(
+                        "could not move window to virtual desktop {}: {:?}",
+                        idx,
+                        error
+                    )
+                })?;
+            }
+        }
+
         let monitor = self
             .focused_monitor_mut()
             .ok_or_else(|| anyhow!("there is no monitor"))?;
         monitor.move_container_to_workspace(idx, follow)?;
         monitor.load_focused_workspace()?;
+
+        if self.virtual_desktop_integration && follow {
+            self.go_to_virtual_desktop(idx)?;
+        }
+
         self.update_focused_workspace(true)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn move_container_to_next_non_empty_workspace(
+        &mut self,
+        direction: CycleDirection,
+    ) -> Result<()> {
+        tracing::info!("moving container");
+
+        let monitor = self
+            .focused_monitor()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let len = monitor.workspaces().len();
+        let mut idx = monitor.focused_workspace_idx();
+        let mut target = None;
+
+        // Every other workspace is checked once, wrapping around in the given direction
+        for _ in 1..len {
+            idx = direction.next_idx(idx, len);
+            if matches!(monitor.workspaces().get(idx), Some(workspace) if !workspace.is_empty()) {
+                target = Option::from(idx);
+                break;
+            }
+        }
+
+        let idx = target.ok_or_else(|| anyhow!("there is no other non-empty workspace"))?;
+        self.move_container_to_workspace(idx, true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_workspace_scratchpad(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+    ) -> Result<()> {
+        tracing::info!("toggling workspace scratchpad");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        if monitor.focused_workspace_idx() == workspace_idx {
+            return Err(anyhow!(
+                "the focused workspace cannot be toggled as a scratchpad"
+            ));
+        }
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        let visible = !workspace.scratchpad_visible();
+        workspace.set_scratchpad_visible(visible);
+
+        if visible {
+            workspace.show_as_scratchpad();
+        } else {
+            workspace.hide();
+        }
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn send_to_named_scratchpad(&mut self, name: String) -> Result<()> {
+        tracing::info!("sending container to scratchpad");
+
+        let container = self
+            .focused_workspace_mut()?
+            .remove_focused_container()
+            .ok_or_else(|| anyhow!("there is no container"))?;
+
+        // If the scratchpad is currently being shown, the container can just stay where it is
+        if !self.visible_scratchpads.contains(&name) {
+            for window in container.windows() {
+                window.hide();
+            }
+        }
+
+        self.scratchpads.entry(name).or_default().push(container);
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_named_scratchpad(&mut self, name: String) -> Result<()> {
+        tracing::info!("toggling scratchpad");
+
+        let work_area = self.focused_monitor_work_area()?;
+
+        let containers = self
+            .scratchpads
+            .get_mut(&name)
+            .ok_or_else(|| anyhow!("there is no scratchpad with this name"))?;
+
+        if let Some(idx) = self.visible_scratchpads.iter().position(|n| n == &name) {
+            self.visible_scratchpads.remove(idx);
+
+            for container in containers.iter() {
+                for window in container.windows() {
+                    window.hide();
+                }
+            }
+
+            return self.update_focused_workspace(true);
+        }
+
+        let mut to_focus = None;
+        for container in containers.iter_mut() {
+            if let Some(window) = container.focused_window_mut() {
+                window.restore();
+                window.center(&work_area)?;
+                to_focus = Option::from(*window);
+            }
+        }
+
+        self.visible_scratchpads.push(name);
+
+        if let Some(window) = to_focus {
+            window.focus()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn scratchpad_contains_window(&self, hwnd: isize) -> bool {
+        self.scratchpads
+            .values()
+            .flatten()
+            .any(|container| container.contains_window(hwnd))
+    }
+
+    pub fn remove_window_from_scratchpads(&mut self, hwnd: isize) {
+        for containers in self.scratchpads.values_mut() {
+            for container in containers.iter_mut() {
+                if let Some(idx) = container.idx_for_window(hwnd) {
+                    container.remove_window_by_idx(idx);
+                }
+            }
+
+            containers.retain(|container| !container.windows().is_empty());
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn focus_container_in_direction(&mut self, direction: OperationDirection) -> Result<()> {
         tracing::info!("focusing container");
@@ -547,6 +1576,70 @@ impl WindowManager {
         Ok(())
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn cycle_container_across_workspaces(&mut self, direction: CycleDirection) -> Result<()> {
+        tracing::info!("cycling container focus across workspaces");
+
+        let monitor = self
+            .focused_monitor()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+        let focused_container_idx = self.focused_workspace()?.focused_container_idx();
+
+        // Every container on the monitor as (workspace_idx, container_idx), in workspace order
+        let containers: Vec<(usize, usize)> = monitor
+            .workspaces()
+            .iter()
+            .enumerate()
+            .flat_map(|(workspace_idx, workspace)| {
+                (0..workspace.containers().len())
+                    .map(move |container_idx| (workspace_idx, container_idx))
+            })
+            .collect();
+
+        if containers.is_empty() {
+            return Err(anyhow!("there are no containers on this monitor"));
+        }
+
+        let current = containers
+            .iter()
+            .position(|&idx| idx == (focused_workspace_idx, focused_container_idx));
+
+        let next = current.map_or_else(
+            // The focused workspace is empty, so cycle from wherever it sits between the others
+            || match direction {
+                CycleDirection::Next => containers
+                    .iter()
+                    .position(|&(workspace_idx, _)| workspace_idx > focused_workspace_idx)
+                    .unwrap_or(0),
+                CycleDirection::Previous => containers
+                    .iter()
+                    .rposition(|&(workspace_idx, _)| workspace_idx < focused_workspace_idx)
+                    .unwrap_or(containers.len() - 1),
+            },
+            |current| direction.next_idx(current, containers.len()),
+        );
+
+        let (workspace_idx, container_idx) = containers[next];
+
+        if workspace_idx == focused_workspace_idx {
+            self.focused_workspace_mut()?.focus_container(container_idx);
+            return self.focused_window_mut()?.focus();
+        }
+
+        // The container is focused before switching so that focus lands on it once the
+        // workspace has been loaded
+        self.focused_monitor_mut()
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?
+            .focus_container(container_idx);
+
+        self.focus_workspace(workspace_idx)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn move_container_in_direction(&mut self, direction: OperationDirection) -> Result<()> {
         tracing::info!("moving container");
@@ -563,6 +1656,75 @@ impl WindowManager {
         self.update_focused_workspace(true)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn focus_window_under_cursor(&mut self) -> Result<()> {
+        tracing::info!("focusing window under cursor");
+
+        let point = WindowsApi::cursor_pos()?;
+
+        let mut target = None;
+        'search: for (i, monitor) in self.monitors().iter().enumerate() {
+            let workspace = monitor
+                .focused_workspace()
+                .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+            for (j, container) in workspace.containers().iter().enumerate() {
+                if let Some(window) = container.focused_window() {
+                    let rect = WindowsApi::window_rect(window.hwnd())?;
+                    if rect.contains_point((point.x, point.y)) {
+                        target = Option::from((i, j, *window));
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        let (monitor_idx, container_idx, window) =
+            target.ok_or_else(|| anyhow!("there is no managed window under the cursor"))?;
+
+        self.focus_monitor(monitor_idx)?;
+        self.focused_workspace_mut()?.focus_container(container_idx);
+        self.update_focused_workspace(false)?;
+
+        // The cursor is already over the window, so there is no need for mouse follows focus
+        window.focus()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn focus_floating_window(&self, direction: CycleDirection) -> Result<()> {
+        tracing::info!("focusing floating window");
+
+        let workspace = self.focused_workspace()?;
+        let floating_windows = workspace.floating_windows();
+
+        if floating_windows.is_empty() {
+            return Err(anyhow!("there are no floating windows on this workspace"));
+        }
+
+        let foreground_hwnd = WindowsApi::foreground_window()?;
+        let next_idx = floating_windows
+            .iter()
+            .position(|w| w.hwnd == foreground_hwnd)
+            .map_or_else(
+                // If a tiled window is focused, start from whichever end of the floating windows
+                // is closest in the given direction
+                || match direction {
+                    CycleDirection::Previous => floating_windows.len() - 1,
+                    CycleDirection::Next => 0,
+                },
+                |idx| direction.next_idx(idx, floating_windows.len()),
+            );
+
+        floating_windows[next_idx].focus()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn focus_tiled_window(&mut self) -> Result<()> {
+        tracing::info!("focusing tiled window");
+
+        self.focused_window_mut()?.focus()
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn cycle_container_window_in_direction(&mut self, direction: CycleDirection) -> Result<()> {
         tracing::info!("cycling container windows");
@@ -582,6 +1744,17 @@ impl WindowManager {
         self.update_focused_workspace(true)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn move_window_in_container_to_position(&mut self, position: usize) -> Result<()> {
+        tracing::info!("moving window within container");
+
+        let container = self.focused_container_mut()?;
+        let focused_idx = container.focused_window_idx();
+        container.move_window_to_position(focused_idx, position)?;
+
+        self.update_focused_workspace(true)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn add_window_to_container(&mut self, direction: OperationDirection) -> Result<()> {
         tracing::info!("adding window to container");
@@ -593,7 +1766,7 @@ impl WindowManager {
             workspace.layout(),
             workspace.layout_flip(),
             workspace.focused_container_idx(),
-            workspace.containers_mut().len(),
+            workspace.containers().len(),
         );
 
         if is_valid {
@@ -623,6 +1796,51 @@ impl WindowManager {
         self.update_focused_workspace(true)
     }
 
+    #[tracing::instrument(skip(self, key))]
+    pub fn reorder_containers(&mut self, key: fn(Window) -> Result<String>) -> Result<()> {
+        tracing::info!("reordering containers");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.sort_containers_by_key(key);
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn rotate_containers(&mut self, direction: CycleDirection) -> Result<()> {
+        tracing::info!("rotating containers");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.rotate_containers(direction);
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn shuffle_containers(&mut self) -> Result<()> {
+        tracing::info!("shuffling containers");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.shuffle_containers()?;
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn pin_focused_window_to_workspace(&mut self) -> Result<()> {
+        tracing::info!("pinning focused window to workspace");
+
+        let monitor_idx = self.focused_monitor_idx();
+        let workspace_idx = self
+            .focused_monitor()
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .focused_workspace_idx();
+
+        let hwnd = self.focused_window_mut()?.hwnd;
+        PINNED_TO_WORKSPACE
+            .lock()
+            .insert(hwnd, (monitor_idx, workspace_idx));
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn remove_window_from_container(&mut self) -> Result<()> {
         tracing::info!("removing window");
@@ -637,6 +1855,42 @@ impl WindowManager {
         self.update_focused_workspace(true)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn split_container(&mut self, at: usize) -> Result<()> {
+        tracing::info!("splitting container");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.split_focused_container(at)?;
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn merge_containers_in_direction(&mut self, direction: OperationDirection) -> Result<()> {
+        tracing::info!("merging containers");
+
+        let workspace = self.focused_workspace_mut()?;
+        let target_idx = workspace
+            .new_idx_for_direction(direction)
+            .ok_or_else(|| anyhow!("this is not a valid direction from the current position"))?;
+
+        workspace.merge_focused_container_with(target_idx)?;
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_container_aspect_ratio(&mut self, aspect_ratio: Option<(u32, u32)>) -> Result<()> {
+        tracing::info!("setting container aspect ratio");
+
+        if let Some((0, _) | (_, 0)) = aspect_ratio {
+            return Err(anyhow!(
+                "an aspect ratio cannot have a width or height of zero"
+            ));
+        }
+
+        self.focused_container_mut()?.set_aspect_ratio(aspect_ratio);
+        self.update_focused_workspace(false)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn toggle_tiling(&mut self) -> Result<()> {
         let workspace = self.focused_workspace_mut()?;
@@ -644,6 +1898,50 @@ impl WindowManager {
         self.update_focused_workspace(false)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_gapless(&mut self) -> Result<()> {
+        tracing::info!("toggling gapless");
+
+        self.focused_workspace_mut()?.toggle_gapless();
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn center_floating_window(&mut self, monitor_idx: Option<usize>) -> Result<()> {
+        tracing::info!("centering floating window");
+
+        let hwnd = WindowsApi::foreground_window()?;
+        let idx = self
+            .focused_workspace()?
+            .floating_windows()
+            .iter()
+            .position(|w| w.hwnd == hwnd)
+            .ok_or_else(|| anyhow!("the focused window is not floating"))?;
+
+        let mut window = self.focused_workspace()?.floating_windows()[idx];
+        let monitor_idx = monitor_idx.unwrap_or_else(|| self.focused_monitor_idx());
+        if monitor_idx >= self.monitors.len() {
+            return Err(anyhow!("this is not a valid monitor index"));
+        }
+
+        // Floating windows belong to the workspace they are displayed on, so the window has to be
+        // handed over to the focused workspace of the target monitor as well
+        if monitor_idx != self.focused_monitor_idx() {
+            self.focused_workspace_mut()?
+                .remove_focused_floating_window();
+
+            self.monitors[monitor_idx]
+                .focused_workspace_mut()
+                .ok_or_else(|| anyhow!("there is no workspace"))?
+                .add_floating_window(window);
+
+            self.focus_monitor(monitor_idx)?;
+        }
+
+        window.center(&self.focused_monitor_work_area()?)?;
+        window.focus()
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn toggle_float(&mut self) -> Result<()> {
         let hwnd = WindowsApi::foreground_window()?;
@@ -694,32 +1992,127 @@ impl WindowManager {
         workspace.new_container_for_floating_window()
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn move_floating_window_to_tile(&mut self, container_idx: usize) -> Result<()> {
+        tracing::info!("moving floating window to tile");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.new_container_for_floating_window_at_idx(container_idx)?;
+        workspace.focus_container(container_idx);
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn tile_all_floating_windows(&mut self) -> Result<()> {
+        tracing::info!("tiling all floating windows");
+
+        let workspace = self.focused_workspace_mut()?;
+        if workspace.floating_windows().is_empty() {
+            return Err(anyhow!(
+                "there are no floating windows on the focused workspace"
+            ));
+        }
+
+        workspace.new_containers_for_floating_windows();
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn float_all_container_windows(&mut self) -> Result<()> {
+        tracing::info!("floating all container windows");
+
+        let work_area = self.focused_monitor_work_area()?;
+
+        let workspace = self.focused_workspace_mut()?;
+        if workspace.containers().is_empty() {
+            return Err(anyhow!("there are no containers on the focused workspace"));
+        }
+
+        let floating_count = workspace.floating_windows().len();
+        workspace.new_floating_windows_for_containers();
+
+        for window in workspace
+            .floating_windows_mut()
+            .iter_mut()
+            .skip(floating_count)
+        {
+            window.center(&work_area)?;
+        }
+
+        self.update_focused_workspace(false)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn toggle_monocle(&mut self) -> Result<()> {
         let workspace = self.focused_workspace_mut()?;
 
-        match workspace.monocle_container() {
-            None => self.monocle_on()?,
-            Some(_) => self.monocle_off()?,
-        }
+        match workspace.monocle_container() {
+            None => self.monocle_on()?,
+            Some(_) => self.monocle_off()?,
+        }
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn monocle_on(&mut self) -> Result<()> {
+        tracing::info!("enabling monocle");
+
+        let include_floating = self.monocle_include_floating;
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.new_monocle_container()?;
+
+        if include_floating {
+            workspace.hide_floating_windows()?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn monocle_off(&mut self) -> Result<()> {
+        tracing::info!("disabling monocle");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.reintegrate_monocle_container()?;
 
-        self.update_focused_workspace(false)
+        // This is a no-op if the floating windows were not hidden when monocle was enabled
+        workspace.restore_floating_windows()
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn monocle_on(&mut self) -> Result<()> {
-        tracing::info!("enabling monocle");
+    pub fn set_monocle_include_floating(&mut self, enable: bool) {
+        tracing::info!("setting monocle include floating");
 
-        let workspace = self.focused_workspace_mut()?;
-        workspace.new_monocle_container()
+        self.monocle_include_floating = enable;
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn monocle_off(&mut self) -> Result<()> {
-        tracing::info!("disabling monocle");
+    pub fn cycle_monocle(&mut self, direction: CycleDirection) -> Result<()> {
+        tracing::info!("cycling monocle container");
 
         let workspace = self.focused_workspace_mut()?;
-        workspace.reintegrate_monocle_container()
+
+        let restore_idx = workspace
+            .monocle_container_restore_idx()
+            .ok_or_else(|| anyhow!("there is no monocle container"))?;
+
+        if workspace.containers().is_empty() {
+            return Err(anyhow!("there are no other containers to cycle to"));
+        }
+
+        // Put the current monocle container back in its original position before picking the
+        // next one, so that cycling repeatedly walks through the containers in workspace order
+        workspace.reintegrate_monocle_container()?;
+
+        let next_idx = direction.next_idx(restore_idx, workspace.containers().len());
+        workspace.focus_container(next_idx);
+        workspace.new_monocle_container()?;
+
+        self.update_focused_workspace(true)
     }
 
     #[tracing::instrument(skip(self))]
@@ -734,6 +2127,14 @@ impl WindowManager {
         self.update_focused_workspace(false)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_soft_maximize(&mut self) -> Result<()> {
+        tracing::info!("toggling soft maximize");
+
+        self.focused_workspace_mut()?.toggle_soft_maximize()?;
+        self.update_focused_workspace(false)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn maximize_window(&mut self) -> Result<()> {
         tracing::info!("maximizing windowj");
@@ -755,40 +2156,7 @@ impl WindowManager {
         tracing::info!("flipping layout");
 
         let workspace = self.focused_workspace_mut()?;
-
-        #[allow(clippy::match_same_arms)]
-        match workspace.layout_flip() {
-            None => {
-                workspace.set_layout_flip(Option::from(layout_flip));
-            }
-            Some(current_layout_flip) => {
-                match current_layout_flip {
-                    Flip::Horizontal => match layout_flip {
-                        Flip::Horizontal => workspace.set_layout_flip(None),
-                        Flip::Vertical => {
-                            workspace.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
-                        }
-                        Flip::HorizontalAndVertical => {
-                            workspace.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
-                        }
-                    },
-                    Flip::Vertical => match layout_flip {
-                        Flip::Horizontal => {
-                            workspace.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
-                        }
-                        Flip::Vertical => workspace.set_layout_flip(None),
-                        Flip::HorizontalAndVertical => {
-                            workspace.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
-                        }
-                    },
-                    Flip::HorizontalAndVertical => match layout_flip {
-                        Flip::Horizontal => workspace.set_layout_flip(Option::from(Flip::Vertical)),
-                        Flip::Vertical => workspace.set_layout_flip(Option::from(Flip::Horizontal)),
-                        Flip::HorizontalAndVertical => workspace.set_layout_flip(None),
-                    },
-                };
-            }
-        }
+        workspace.flip_layout(layout_flip);
 
         self.update_focused_workspace(false)
     }
@@ -798,7 +2166,67 @@ impl WindowManager {
         tracing::info!("changing layout");
 
         let workspace = self.focused_workspace_mut()?;
-        workspace.set_layout(layout);
+        workspace.change_layout(with_columns_main_ratio(layout));
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn undo_layout_change(&mut self) -> Result<()> {
+        tracing::info!("undoing layout change");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.undo_layout_change()?;
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn undo_flip_layout(&mut self) -> Result<()> {
+        tracing::info!("undoing layout flip");
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.undo_flip_layout();
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_three_column_ratio(&mut self, main_ratio: u32, secondary_ratio: u32) -> Result<()> {
+        tracing::info!("setting three-column layout ratio");
+
+        if main_ratio == 0 {
+            return Err(anyhow!("the main column ratio must be greater than zero"));
+        }
+
+        let workspace = self.focused_workspace_mut()?;
+
+        if !matches!(workspace.layout(), Layout::ThreeColumn { .. }) {
+            return Err(anyhow!(
+                "the focused workspace is not using the three-column layout"
+            ));
+        }
+
+        workspace.set_layout(Layout::ThreeColumn {
+            main_ratio,
+            secondary_ratio,
+        });
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_columns_main_ratio(&mut self, ratio: f32) -> Result<()> {
+        tracing::info!("setting columns layout main ratio");
+
+        if ratio <= 0.0 || ratio >= 1.0 {
+            return Err(anyhow!("the main column ratio must be between 0 and 1"));
+        }
+
+        *COLUMNS_MAIN_RATIO.lock() = ratio;
+
+        let workspace = self.focused_workspace_mut()?;
+        if matches!(workspace.layout(), Layout::Columns { .. }) {
+            workspace.set_layout(with_columns_main_ratio(Layout::default_columns()));
+        }
+
         self.update_focused_workspace(false)
     }
 
@@ -855,66 +2283,281 @@ impl WindowManager {
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn set_workspace_layout(
+    pub fn set_workspace_mouse_follows_focus(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        enable: bool,
+    ) -> Result<()> {
+        tracing::info!("setting workspace mouse follows focus");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        workspace.set_mouse_follows_focus(Option::from(enable));
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_layout(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        layout: Layout,
+    ) -> Result<()> {
+        tracing::info!("setting workspace layout");
+
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let work_area = monitor.work_area_with_offset();
+        let scale_factor = monitor.padding_scale_factor();
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        workspace.change_layout(with_columns_main_ratio(layout));
+
+        // If this is the focused workspace on a non-focused screen, let's update it
+        if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
+            workspace.update(&work_area, scale_factor)?;
+            Ok(())
+        } else {
+            Ok(self.update_focused_workspace(false)?)
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_default_layout(
+        &mut self,
+        monitor_idx: usize,
+        layout: Layout,
+    ) -> Result<()> {
+        tracing::info!("setting workspace default layout");
+
+        self.monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .set_default_layout(Option::from(layout));
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_max_workspaces(&mut self, monitor_idx: usize, max: usize) -> Result<()> {
+        tracing::info!("setting maximum workspaces");
+
+        self.monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .set_max_workspaces(Option::from(max));
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_layout_flip(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        layout_flip: Flip,
+    ) -> Result<()> {
+        tracing::info!("setting workspace layout flip");
+
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let work_area = monitor.work_area_with_offset();
+        let scale_factor = monitor.padding_scale_factor();
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        workspace.flip_layout(layout_flip);
+
+        // If this is the focused workspace on a non-focused screen, let's update it
+        if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
+            workspace.update(&work_area, scale_factor)?;
+            Ok(())
+        } else {
+            Ok(self.update_focused_workspace(false)?)
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_monitor_work_area_offset(&mut self, monitor_idx: usize, offset: Rect) -> Result<()> {
+        tracing::info!("setting monitor work area offset");
+
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        monitor.set_work_area_offset(offset);
+
+        if focused_monitor_idx == monitor_idx {
+            self.update_focused_workspace(false)
+        } else {
+            monitor.update_focused_workspace()
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn ensure_workspaces_for_monitor(
+        &mut self,
+        monitor_idx: usize,
+        workspace_count: usize,
+    ) -> Result<()> {
+        tracing::info!("ensuring workspace count");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        monitor.ensure_workspace_count(workspace_count)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn ensure_workspaces_for_all_monitors(&mut self, workspace_count: usize) -> Result<()> {
+        tracing::info!("ensuring workspace count for all monitors");
+
+        for monitor in self.monitors_mut() {
+            monitor.ensure_workspace_count(workspace_count)?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_padding_from_edge(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        padding: Rect,
+    ) -> Result<()> {
+        tracing::info!("setting workspace padding from edge");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
+
+        workspace.set_workspace_padding_from_edge(Option::from(padding));
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_padding(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        size: i32,
+    ) -> Result<()> {
+        tracing::info!("setting workspace padding");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        workspace.set_workspace_padding(Option::from(size));
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_name(
         &mut self,
         monitor_idx: usize,
         workspace_idx: usize,
-        layout: Layout,
+        name: String,
     ) -> Result<()> {
-        tracing::info!("setting workspace layout");
-
-        let focused_monitor_idx = self.focused_monitor_idx();
+        tracing::info!("setting workspace name");
 
         let monitor = self
             .monitors_mut()
             .get_mut(monitor_idx)
             .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        let work_area = *monitor.work_area_size();
-        let focused_workspace_idx = monitor.focused_workspace_idx();
-
         let workspace = monitor
             .workspaces_mut()
             .get_mut(workspace_idx)
             .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        workspace.set_layout(layout);
+        workspace.set_name(Option::from(name.clone()));
+        monitor.workspace_names_mut().insert(workspace_idx, name);
 
-        // If this is the focused workspace on a non-focused screen, let's update it
-        if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
-            workspace.update(&work_area)?;
-            Ok(())
-        } else {
-            Ok(self.update_focused_workspace(false)?)
-        }
+        Ok(())
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn ensure_workspaces_for_monitor(
+    pub fn set_container_padding(
         &mut self,
         monitor_idx: usize,
-        workspace_count: usize,
+        workspace_idx: usize,
+        size: i32,
     ) -> Result<()> {
-        tracing::info!("ensuring workspace count");
+        tracing::info!("setting container padding");
 
         let monitor = self
             .monitors_mut()
             .get_mut(monitor_idx)
             .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        monitor.ensure_workspace_count(workspace_count);
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        Ok(())
+        workspace.set_container_padding(Option::from(size));
+
+        self.update_focused_workspace(false)
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn set_workspace_padding(
+    pub fn set_workspace_gap(
         &mut self,
         monitor_idx: usize,
         workspace_idx: usize,
         size: i32,
     ) -> Result<()> {
-        tracing::info!("setting workspace padding");
+        tracing::info!("setting workspace gap");
 
         let monitor = self
             .monitors_mut()
@@ -924,82 +2567,419 @@ impl WindowManager {
         let workspace = monitor
             .workspaces_mut()
             .get_mut(workspace_idx)
-            .ok_or_else(|| anyhow!("there is no monitor"))?;
+            .ok_or_else(|| anyhow!("there is no workspace"))?;
 
-        workspace.set_workspace_padding(Option::from(size));
+        workspace.set_gap(Option::from(size));
 
         self.update_focused_workspace(false)
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn set_workspace_name(
+    pub fn set_container_padding_for_monitor(
         &mut self,
         monitor_idx: usize,
-        workspace_idx: usize,
-        name: String,
+        size: i32,
     ) -> Result<()> {
-        tracing::info!("setting workspace name");
+        tracing::info!("setting container padding for all workspaces on monitor");
 
         let monitor = self
             .monitors_mut()
             .get_mut(monitor_idx)
             .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        let workspace = monitor
-            .workspaces_mut()
-            .get_mut(workspace_idx)
-            .ok_or_else(|| anyhow!("there is no monitor"))?;
-
-        workspace.set_name(Option::from(name.clone()));
-        monitor.workspace_names_mut().insert(workspace_idx, name);
+        for workspace in monitor.workspaces_mut() {
+            workspace.set_container_padding(Option::from(size));
+        }
 
-        Ok(())
+        self.update_focused_workspace(false)
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn set_container_padding(
+    pub fn set_workspace_padding_for_monitor(
         &mut self,
         monitor_idx: usize,
-        workspace_idx: usize,
         size: i32,
     ) -> Result<()> {
-        tracing::info!("setting container padding");
+        tracing::info!("setting workspace padding for all workspaces on monitor");
 
         let monitor = self
             .monitors_mut()
             .get_mut(monitor_idx)
             .ok_or_else(|| anyhow!("there is no monitor"))?;
 
-        let workspace = monitor
-            .workspaces_mut()
-            .get_mut(workspace_idx)
-            .ok_or_else(|| anyhow!("there is no monitor"))?;
+        for workspace in monitor.workspaces_mut() {
+            workspace.set_workspace_padding(Option::from(size));
+        }
 
-        workspace.set_container_padding(Option::from(size));
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_global_container_padding(&mut self, size: i32) -> Result<()> {
+        tracing::info!("setting container padding for all workspaces");
+
+        // Workspaces created after this point should also pick up the new padding
+        *DEFAULT_CONTAINER_PADDING.lock() = Option::from(size);
+
+        for monitor in self.monitors_mut() {
+            for workspace in monitor.workspaces_mut() {
+                workspace.set_container_padding(Option::from(size));
+            }
+        }
+
+        self.update_focused_workspace(false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_global_workspace_padding(&mut self, size: i32) -> Result<()> {
+        tracing::info!("setting workspace padding for all workspaces");
+
+        *DEFAULT_WORKSPACE_PADDING.lock() = Option::from(size);
+
+        for monitor in self.monitors_mut() {
+            for workspace in monitor.workspaces_mut() {
+                workspace.set_workspace_padding(Option::from(size));
+            }
+        }
 
         self.update_focused_workspace(false)
     }
 
     pub fn focused_monitor_work_area(&self) -> Result<Rect> {
-        Ok(*self
+        Ok(self
             .focused_monitor()
             .ok_or_else(|| anyhow!("there is no monitor"))?
-            .work_area_size())
+            .work_area_with_offset())
     }
 
     #[tracing::instrument(skip(self))]
     pub fn focus_monitor(&mut self, idx: usize) -> Result<()> {
         tracing::info!("focusing monitor");
 
-        if self.monitors().get(idx).is_some() {
-            self.monitors.focus(idx);
-        } else {
+        if idx >= self.monitors.len() {
             return Err(anyhow!("this is not a valid monitor index"));
         }
 
+        self.monitors.focus(idx);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn map_monitor_to_index(&mut self, name: String, idx: usize) -> Result<()> {
+        tracing::info!("mapping monitor to index");
+
+        MONITOR_INDEX_PREFERENCES.lock().insert(name, idx);
+
+        let focused_monitor_id = self
+            .focused_monitor()
+            .ok_or_else(|| anyhow!("there is no monitor"))?
+            .id();
+
+        // Monitors take their workspaces with them when they are reordered, so the only thing
+        // that needs fixing up afterwards is the index of the focused monitor
+        monitor::apply_index_preferences(self.monitors.elements_mut());
+
+        let focused_idx = self
+            .monitors
+            .iter()
+            .position(|m| m.id() == focused_monitor_id)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        self.focus_monitor(focused_idx)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_pause_monitor(&mut self, idx: usize) -> Result<()> {
+        tracing::info!("toggling pause for monitor");
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        let paused = !monitor.paused();
+        monitor.set_paused(paused);
+
+        // Windows may have been moved or resized while we weren't looking
+        if !paused {
+            monitor.update_focused_workspace()?;
+        }
+
         Ok(())
     }
 
+    pub fn monitor_idx_in_direction(&self, direction: OperationDirection) -> Option<usize> {
+        let current_idx = self.focused_monitor_idx();
+        let current = *self.focused_monitor()?.work_area_size();
+
+        // A monitor is only considered adjacent if it overlaps with the focused monitor on the
+        // axis perpendicular to the direction, so that monitors of different heights or
+        // orientations are still matched correctly
+        let overlaps_vertically =
+            |r: &Rect| r.top < current.top + current.bottom && current.top < r.top + r.bottom;
+        let overlaps_horizontally =
+            |r: &Rect| r.left < current.left + current.right && current.left < r.left + r.right;
+
+        let mut closest: Option<(usize, i32)> = None;
+
+        for (i, monitor) in self.monitors().iter().enumerate() {
+            if i == current_idx {
+                continue;
+            }
+
+            let r = monitor.work_area_size();
+            let distance = match direction {
+                OperationDirection::Left if overlaps_vertically(r) => {
+                    current.left - (r.left + r.right)
+                }
+                OperationDirection::Right if overlaps_vertically(r) => {
+                    r.left - (current.left + current.right)
+                }
+                OperationDirection::Up if overlaps_horizontally(r) => {
+                    current.top - (r.top + r.bottom)
+                }
+                OperationDirection::Down if overlaps_horizontally(r) => {
+                    r.top - (current.top + current.bottom)
+                }
+                _ => continue,
+            };
+
+            if distance < 0 {
+                continue;
+            }
+
+            if closest.map_or(true, |(_, closest_distance)| distance < closest_distance) {
+                closest = Option::from((i, distance));
+            }
+        }
+
+        closest.map(|(i, _)| i)
+    }
+
+    pub fn workspace_by_idx(&self, monitor_idx: usize, workspace_idx: usize) -> Option<&Workspace> {
+        self.monitors()
+            .get(monitor_idx)
+            .and_then(|monitor| monitor.workspaces().get(workspace_idx))
+    }
+
+    // The cached location is only ever treated as a hint, because container indices shift whenever
+    // containers are added, removed or reordered, so it is checked against the tree before use
+    pub fn workspace_for_window(&self, hwnd: isize) -> Option<(usize, usize)> {
+        let cached = WINDOW_LOCATION_CACHE.lock().get(&hwnd).copied();
+        if let Some((monitor_idx, workspace_idx, _)) = cached {
+            if matches!(
+                self.workspace_by_idx(monitor_idx, workspace_idx),
+                Some(workspace) if workspace.contains_window(hwnd)
+            ) {
+                return Option::from((monitor_idx, workspace_idx));
+            }
+        }
+
+        for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+                if workspace.contains_window(hwnd) {
+                    // Floating, monocle and maximized windows don't have a container index
+                    if let Some(container_idx) = workspace
+                        .containers()
+                        .iter()
+                        .position(|container| container.contains_window(hwnd))
+                    {
+                        WINDOW_LOCATION_CACHE
+                            .lock()
+                            .insert(hwnd, (monitor_idx, workspace_idx, container_idx));
+                    }
+
+                    return Option::from((monitor_idx, workspace_idx));
+                }
+            }
+        }
+
+        WINDOW_LOCATION_CACHE.lock().remove(&hwnd);
+        None
+    }
+
+    pub fn window_location(&self, hwnd: isize) -> Option<WindowLocation> {
+        let (monitor_idx, workspace_idx) = self.workspace_for_window(hwnd)?;
+        let workspace = self.workspace_by_idx(monitor_idx, workspace_idx)?;
+
+        let cached = WINDOW_LOCATION_CACHE.lock().get(&hwnd).copied();
+        if let Some((_, _, container_idx)) = cached {
+            if let Some(window_idx) = workspace
+                .containers()
+                .get(container_idx)
+                .and_then(|container| container.idx_for_window(hwnd))
+            {
+                return Option::from(WindowLocation {
+                    monitor_idx,
+                    workspace_idx,
+                    container_idx,
+                    window_idx,
+                });
+            }
+        }
+
+        let mut containers: Vec<(usize, &Container)> =
+            workspace.containers().iter().enumerate().collect();
+
+        // A monocle container is reported at the index it will be restored to
+        if let (Some(container), Some(idx)) = (
+            workspace.monocle_container(),
+            workspace.monocle_container_restore_idx(),
+        ) {
+            containers.push((idx, container));
+        }
+
+        for (container_idx, container) in containers {
+            if let Some(window_idx) = container.idx_for_window(hwnd) {
+                return Option::from(WindowLocation {
+                    monitor_idx,
+                    workspace_idx,
+                    container_idx,
+                    window_idx,
+                });
+            }
+        }
+
+        None
+    }
+
+    pub fn managed_status(&self, hwnd: isize) -> ManagedStatus {
+        self.workspace_for_window(hwnd)
+            .and_then(|(monitor_idx, workspace_idx)| {
+                self.workspace_by_idx(monitor_idx, workspace_idx)
+            })
+            .map_or(
+                ManagedStatus {
+                    managed: false,
+                    floating: false,
+                },
+                |workspace| ManagedStatus {
+                    managed: true,
+                    floating: workspace.floating_windows().iter().any(|w| w.hwnd == hwnd),
+                },
+            )
+    }
+
+    // GetWindowThreadProcessId returns a thread id of 0 if the HWND is invalid, which can still
+    // happen for a managed window that was destroyed before komorebi processed the event
+    pub fn process_id_for_window(&self, hwnd: isize) -> Option<WindowProcessId> {
+        self.workspace_for_window(hwnd)?;
+
+        let (pid, tid) = WindowsApi::window_thread_process_id(Window { hwnd }.hwnd());
+        if tid == 0 {
+            return None;
+        }
+
+        Option::from(WindowProcessId { pid, tid })
+    }
+
+    pub fn window_tree(&self) -> serde_json::Value {
+        let mut monitors = vec![];
+
+        for monitor in self.monitors() {
+            let mut workspaces = vec![];
+
+            for workspace in monitor.workspaces() {
+                let mut containers = vec![];
+
+                for container in workspace.containers() {
+                    containers.push(json!({
+                        "id": container.id(),
+                        "focused_window": container.focused_window_idx(),
+                        "windows": container.windows(),
+                    }));
+                }
+
+                workspaces.push(json!({
+                    "name": workspace.name(),
+                    "layout": workspace.layout(),
+                    "layout_flip": workspace.layout_flip(),
+                    "workspace_padding": workspace.workspace_padding(),
+                    "workspace_padding_from_edge": workspace.workspace_padding_from_edge(),
+                    "container_padding": workspace.container_padding(),
+                    "gap": workspace.gap(),
+                    "resize_dimensions": workspace.resize_dimensions(),
+                    "latest_layout": workspace.latest_layout(),
+                    "visible_window_rects": workspace.visible_window_rects(),
+                    "tile": workspace.tile(),
+                    "focused_container": workspace.focused_container_idx(),
+                    "containers": containers,
+                    "monocle_container": workspace.monocle_container(),
+                    "maximized_window": workspace.maximized_window(),
+                    "floating_windows": workspace.floating_windows(),
+                }));
+            }
+
+            monitors.push(json!({
+                "id": monitor.id(),
+                "name": monitor.name(),
+                "monitor_size": monitor.monitor_size(),
+                "work_area_size": monitor.work_area_size(),
+                "work_area_offset": monitor.work_area_offset(),
+                "is_primary": monitor.is_primary(),
+                "paused": monitor.paused(),
+                "focused_workspace": monitor.focused_workspace_idx(),
+                "workspaces": workspaces,
+            }));
+        }
+
+        json!({
+            "focused_monitor": self.focused_monitor_idx(),
+            "monitors": monitors,
+        })
+    }
+
+    pub fn monitor_information(&self) -> Result<Vec<MonitorInformation>> {
+        let mut information = vec![];
+
+        for monitor in self.monitors() {
+            information.push(MonitorInformation {
+                id: monitor.id(),
+                device_name: monitor.name().clone(),
+                work_area: *monitor.work_area_size(),
+                monitor_area: *monitor.monitor_size(),
+                dpi: WindowsApi::dpi_for_monitor(monitor.id())?,
+                is_primary: monitor.is_primary(),
+            });
+        }
+
+        Ok(information)
+    }
+
+    pub fn primary_monitor_idx(&self) -> Option<usize> {
+        self.monitors.iter().position(Monitor::is_primary)
+    }
+
+    pub fn focused_workspace_index(&self) -> Result<FocusedWorkspaceIndex> {
+        let monitor = self
+            .focused_monitor()
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        Ok(FocusedWorkspaceIndex {
+            monitor_idx: self.focused_monitor_idx(),
+            workspace_idx: monitor.focused_workspace_idx(),
+            name: self.focused_workspace()?.name().clone(),
+        })
+    }
+
+    pub fn workspace_window_counts(&self) -> Vec<Vec<WorkspaceWindowCount>> {
+        self.monitors()
+            .iter()
+            .map(|monitor| {
+                monitor
+                    .workspaces()
+                    .iter()
+                    .map(|workspace| WorkspaceWindowCount {
+                        containers: workspace.containers().len(),
+                        floating: workspace.floating_windows().len(),
+                        name: workspace.name().clone(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     pub fn monitor_idx_from_window(&mut self, window: Window) -> Option<usize> {
         let hmonitor = WindowsApi::monitor_from_window(window.hwnd());
 
@@ -1030,6 +3010,10 @@ impl WindowManager {
     pub fn focus_workspace(&mut self, idx: usize) -> Result<()> {
         tracing::info!("focusing workspace");
 
+        if self.virtual_desktop_integration {
+            Self::ensure_virtual_desktop_for_workspace(idx)?;
+        }
+
         let monitor = self
             .focused_monitor_mut()
             .ok_or_else(|| anyhow!("there is no workspace"))?;
@@ -1037,20 +3021,57 @@ impl WindowManager {
         monitor.focus_workspace(idx)?;
         monitor.load_focused_workspace()?;
 
+        if self.virtual_desktop_integration {
+            self.go_to_virtual_desktop(idx)?;
+        }
+
+        self.update_focused_workspace(true)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_focused_monitor_workspace(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+    ) -> Result<()> {
+        tracing::info!("focusing workspace on monitor");
+
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_else(|| anyhow!("there is no monitor"))?;
+
+        monitor.focus_workspace(workspace_idx)?;
+        monitor.load_focused_workspace()?;
+
+        if monitor_idx != focused_monitor_idx {
+            monitor.update_focused_workspace()?;
+        }
+
+        // Loading the workspace will have focused one of its windows, so we need to make sure
+        // that focus is given back to the focused monitor
         self.update_focused_workspace(true)
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn new_workspace(&mut self) -> Result<()> {
+    pub fn new_workspace(&mut self, name: Option<String>) -> Result<()> {
         tracing::info!("adding new workspace");
 
+        let monitor_idx = self.focused_monitor_idx();
         let monitor = self
             .focused_monitor_mut()
             .ok_or_else(|| anyhow!("there is no workspace"))?;
 
-        monitor.focus_workspace(monitor.new_workspace_idx())?;
+        let workspace_idx = monitor.new_workspace_idx()?;
+        monitor.focus_workspace(workspace_idx)?;
         monitor.load_focused_workspace()?;
 
+        if let Some(name) = name {
+            self.set_workspace_name(monitor_idx, workspace_idx, name)?;
+        }
+
         self.update_focused_workspace(true)
     }
 
@@ -1072,3 +3093,133 @@ impl WindowManager {
             .ok_or_else(|| anyhow!("there is no window"))
     }
 }
+
+fn saved_state_path() -> Result<PathBuf> {
+    let mut path = dirs::home_dir().ok_or_else(|| anyhow!("there is no home directory"))?;
+    path.push("komorebi.state.json");
+    Ok(path)
+}
+
+fn restore_title_bars() -> Result<()> {
+    let title_barless = TITLE_BARLESS_HWNDS.lock().clone();
+
+    for hwnd in title_barless {
+        let window = Window { hwnd };
+
+        // Windows that have since been closed can just be forgotten about
+        if window.is_window() {
+            window.restore_title_bar()?;
+        } else {
+            TITLE_BARLESS_HWNDS.lock().retain(|h| *h != hwnd);
+        }
+    }
+
+    Ok(())
+}
+
+fn restore_window_decorations() -> Result<()> {
+    let decorationless = DECORATIONLESS_HWNDS.lock().clone();
+
+    for hwnd in decorationless {
+        let window = Window { hwnd };
+
+        // Windows that have since been closed can just be forgotten about
+        if window.is_window() {
+            window.toggle_decorations()?;
+        } else {
+            DECORATIONLESS_HWNDS.lock().retain(|h| *h != hwnd);
+        }
+    }
+
+    Ok(())
+}
+
+// Don't allow any container to be resized to the point where its windows become unusable. If the
+// target resize adjustment would make a container too small, the adjustment is moved from its
+// current value towards the target only as far as it can go while every container stays at or
+// above the minimum size
+fn clamp_resize_to_minimum_size(
+    current: Option<Rect>,
+    target: Option<Rect>,
+    calculate: impl Fn(Option<Rect>) -> Result<Vec<Rect>>,
+) -> Result<Option<Rect>> {
+    let (minimum_width, minimum_height) = *MINIMUM_CONTAINER_SIZE.lock();
+    let (minimum_width, minimum_height) = (
+        i32::try_from(minimum_width)?,
+        i32::try_from(minimum_height)?,
+    );
+
+    let fits = |resize: Option<Rect>| -> Result<bool> {
+        Ok(calculate(resize)?
+            .iter()
+            .all(|rect| rect.right >= minimum_width && rect.bottom >= minimum_height))
+    };
+
+    if fits(target)? {
+        return Ok(target);
+    }
+
+    let from = current.unwrap_or_default();
+    let to = target.unwrap_or_default();
+    let steps = [
+        to.left - from.left,
+        to.top - from.top,
+        to.right - from.right,
+        to.bottom - from.bottom,
+    ]
+    .iter()
+    .map(|delta| delta.abs())
+    .max()
+    .unwrap_or_default();
+
+    let partial = |step: i32| {
+        let towards = |from: i32, to: i32| from + (to - from) * step / steps;
+        let resize = Rect {
+            left: towards(from.left, to.left),
+            top: towards(from.top, to.top),
+            right: towards(from.right, to.right),
+            bottom: towards(from.bottom, to.bottom),
+        };
+
+        if resize.eq(&Rect::default()) {
+            None
+        } else {
+            Option::from(resize)
+        }
+    };
+
+    if !fits(current)? {
+        tracing::warn!("cannot resize container below the minimum container size");
+        return Ok(current);
+    }
+
+    // The current adjustment fits and the target doesn't, so the furthest step that still fits
+    // is somewhere in between
+    let (mut fitting, mut too_small) = (0, steps);
+    while too_small - fitting > 1 {
+        let step = fitting + (too_small - fitting) / 2;
+        if fits(partial(step))? {
+            fitting = step;
+        } else {
+            too_small = step;
+        }
+    }
+
+    if fitting == 0 {
+        Ok(current)
+    } else {
+        Ok(partial(fitting))
+    }
+}
+
+// Layouts selected from the command line always come with the default main ratio for the columns
+// layout, so the ratio that was last set is filled in here instead
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn with_columns_main_ratio(layout: Layout) -> Layout {
+    match layout {
+        Layout::Columns { .. } => Layout::Columns {
+            main_ratio: (*COLUMNS_MAIN_RATIO.lock() * 100.0).round() as u32,
+        },
+        layout => layout,
+    }
+}
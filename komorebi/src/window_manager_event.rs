@@ -1,11 +1,19 @@
 use std::fmt::Display;
 use std::fmt::Formatter;
 
+use komorebi_core::WindowManagerEventType;
+
 use crate::window::Window;
 use crate::winevent::WinEvent;
+use crate::AUTO_MANAGE;
+use crate::DEBOUNCED_EVENTS;
+use crate::KNOWN_TITLES;
+use crate::LAST_EVENT_TIMES;
 use crate::OBJECT_NAME_CHANGE_ON_LAUNCH;
+use crate::PINNED_TO_WORKSPACE;
+use crate::WINDOW_LOCATION_CACHE;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum WindowManagerEvent {
     Destroy(WinEvent, Window),
     FocusChange(WinEvent, Window),
@@ -14,6 +22,7 @@ pub enum WindowManagerEvent {
     Show(WinEvent, Window),
     MoveResizeEnd(WinEvent, Window),
     MouseCapture(WinEvent, Window),
+    TitleChange(Window, String),
     Manage(Window),
     Unmanage(Window),
 }
@@ -60,13 +69,20 @@ impl Display for WindowManagerEvent {
                     winevent, window
                 )
             }
+            WindowManagerEvent::TitleChange(window, old_title) => {
+                write!(
+                    f,
+                    "TitleChange (Window: {}, Old Title: {})",
+                    window, old_title
+                )
+            }
         }
     }
 }
 
 impl WindowManagerEvent {
-    pub const fn window(self) -> Window {
-        match self {
+    pub const fn window(&self) -> Window {
+        match *self {
             WindowManagerEvent::Destroy(_, window)
             | WindowManagerEvent::FocusChange(_, window)
             | WindowManagerEvent::Hide(_, window)
@@ -74,14 +90,42 @@ impl WindowManagerEvent {
             | WindowManagerEvent::Show(_, window)
             | WindowManagerEvent::MoveResizeEnd(_, window)
             | WindowManagerEvent::MouseCapture(_, window)
+            | WindowManagerEvent::TitleChange(window, _)
             | WindowManagerEvent::Manage(window)
             | WindowManagerEvent::Unmanage(window) => window,
         }
     }
 
+    // Manage and Unmanage are only ever sent by the user, so they can never be suppressed
+    pub const fn event_type(&self) -> Option<WindowManagerEventType> {
+        match self {
+            WindowManagerEvent::Destroy(_, _) => Some(WindowManagerEventType::Destroy),
+            WindowManagerEvent::FocusChange(_, _) => Some(WindowManagerEventType::FocusChange),
+            WindowManagerEvent::Hide(_, _) => Some(WindowManagerEventType::Hide),
+            WindowManagerEvent::Minimize(_, _) => Some(WindowManagerEventType::Minimize),
+            WindowManagerEvent::Show(_, _) => Some(WindowManagerEventType::Show),
+            WindowManagerEvent::MoveResizeEnd(_, _) => Some(WindowManagerEventType::MoveResizeEnd),
+            WindowManagerEvent::MouseCapture(_, _) => Some(WindowManagerEventType::MouseCapture),
+            WindowManagerEvent::TitleChange(_, _) => Some(WindowManagerEventType::TitleChange),
+            WindowManagerEvent::Manage(_) | WindowManagerEvent::Unmanage(_) => None,
+        }
+    }
+
     pub fn from_win_event(winevent: WinEvent, window: Window) -> Option<Self> {
         match winevent {
-            WinEvent::ObjectDestroy => Option::from(Self::Destroy(winevent, window)),
+            WinEvent::ObjectDestroy => {
+                KNOWN_TITLES.lock().remove(&window.hwnd);
+                window.invalidate_border_rect();
+                PINNED_TO_WORKSPACE.lock().remove(&window.hwnd);
+                WINDOW_LOCATION_CACHE.lock().remove(&window.hwnd);
+                LAST_EVENT_TIMES
+                    .lock()
+                    .retain(|(_, hwnd), _| *hwnd != window.hwnd);
+                DEBOUNCED_EVENTS
+                    .lock()
+                    .retain(|(_, hwnd), _| *hwnd != window.hwnd);
+                Option::from(Self::Destroy(winevent, window))
+            }
 
             WinEvent::ObjectCloaked | WinEvent::ObjectHide => {
                 Option::from(Self::Hide(winevent, window))
@@ -108,10 +152,24 @@ impl WindowManagerEvent {
                 // [yatta\src\windows_event.rs:110] event = 32780 ObjectNameChange
                 // [yatta\src\windows_event.rs:110] event = 32779 ObjectLocationChange
 
+                // When auto-manage is enabled, any window can be picked up this way, which catches
+                // windows that only set their real title after they have already been shown
                 let object_name_change_on_launch = OBJECT_NAME_CHANGE_ON_LAUNCH.lock();
 
-                if object_name_change_on_launch.contains(&window.exe().ok()?) {
-                    Option::from(Self::Show(winevent, window))
+                if *AUTO_MANAGE.lock() || object_name_change_on_launch.contains(&window.exe().ok()?)
+                {
+                    let title = window.title().ok()?;
+                    let old_title = KNOWN_TITLES
+                        .lock()
+                        .insert(window.hwnd, title.clone())
+                        .unwrap_or_default();
+
+                    // These events are also sent for changes that don't touch the title text
+                    if title == old_title {
+                        None
+                    } else {
+                        Option::from(Self::TitleChange(window, old_title))
+                    }
                 } else {
                     None
                 }
@@ -15,6 +15,7 @@ use bindings::Windows::Win32::Foundation::POINT;
 use bindings::Windows::Win32::Foundation::PWSTR;
 use bindings::Windows::Win32::Foundation::RECT;
 use bindings::Windows::Win32::Graphics::Dwm::DwmGetWindowAttribute;
+use bindings::Windows::Win32::Graphics::Dwm::DwmSetWindowAttribute;
 use bindings::Windows::Win32::Graphics::Dwm::DWMWA_CLOAKED;
 use bindings::Windows::Win32::Graphics::Dwm::DWMWA_EXTENDED_FRAME_BOUNDS;
 use bindings::Windows::Win32::Graphics::Dwm::DWMWINDOWATTRIBUTE;
@@ -27,7 +28,7 @@ use bindings::Windows::Win32::Graphics::Gdi::MonitorFromWindow;
 use bindings::Windows::Win32::Graphics::Gdi::HDC;
 use bindings::Windows::Win32::Graphics::Gdi::HMONITOR;
 use bindings::Windows::Win32::Graphics::Gdi::MONITORENUMPROC;
-use bindings::Windows::Win32::Graphics::Gdi::MONITORINFO;
+use bindings::Windows::Win32::Graphics::Gdi::MONITORINFOEXW;
 use bindings::Windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONEAREST;
 use bindings::Windows::Win32::System::Threading::AttachThreadInput;
 use bindings::Windows::Win32::System::Threading::GetCurrentProcessId;
@@ -37,6 +38,8 @@ use bindings::Windows::Win32::System::Threading::QueryFullProcessImageNameW;
 use bindings::Windows::Win32::System::Threading::PROCESS_ACCESS_RIGHTS;
 use bindings::Windows::Win32::System::Threading::PROCESS_NAME_FORMAT;
 use bindings::Windows::Win32::System::Threading::PROCESS_QUERY_INFORMATION;
+use bindings::Windows::Win32::UI::HiDpi::GetDpiForMonitor;
+use bindings::Windows::Win32::UI::HiDpi::MDT_EFFECTIVE_DPI;
 use bindings::Windows::Win32::UI::KeyboardAndMouseInput::SetFocus;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::AllowSetForegroundWindow;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::EnumWindows;
@@ -55,6 +58,7 @@ use bindings::Windows::Win32::UI::WindowsAndMessaging::IsWindowVisible;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::RealGetWindowClassW;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+use bindings::Windows::Win32::UI::WindowsAndMessaging::SetLayeredWindowAttributes;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SetWindowLongPtrW;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SetWindowPos;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::ShowWindow;
@@ -64,6 +68,8 @@ use bindings::Windows::Win32::UI::WindowsAndMessaging::GWL_STYLE;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::GW_HWNDNEXT;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::HWND_NOTOPMOST;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::HWND_TOPMOST;
+use bindings::Windows::Win32::UI::WindowsAndMessaging::LWA_ALPHA;
+use bindings::Windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SET_WINDOW_POS_FLAGS;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SHOW_WINDOW_CMD;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SPIF_SENDCHANGE;
@@ -168,7 +174,13 @@ impl WindowsApi {
         Self::enum_display_monitors(
             windows_callbacks::enum_display_monitor,
             monitors as *mut Ring<Monitor> as isize,
-        )
+        )?;
+
+        // The enumeration order can change whenever Windows feels like it, so any user-specified
+        // indices need to be applied to keep workspace rules etc. pointing at the right monitors
+        monitor::apply_index_preferences(monitors.elements_mut());
+
+        Ok(())
     }
 
     pub fn enum_windows(callback: WNDENUMPROC, callback_data_address: isize) -> Result<()> {
@@ -208,6 +220,8 @@ impl WindowsApi {
                     workspace.remove_window(hwnd)?;
                 }
 
+                workspace.sync_windows_set();
+
                 monitor.workspaces_mut().push_back(workspace);
             }
         }
@@ -248,6 +262,16 @@ impl WindowsApi {
         }))
     }
 
+    pub fn redraw_frame(hwnd: HWND) -> Result<()> {
+        let flags = SetWindowPosition::NO_MOVE
+            | SetWindowPosition::NO_SIZE
+            | SetWindowPosition::NO_Z_ORDER
+            | SetWindowPosition::NO_ACTIVATE
+            | SetWindowPosition::FRAME_CHANGED;
+
+        Self::set_window_pos(hwnd, &Rect::default(), HWND::default(), flags.bits())
+    }
+
     fn show_window(hwnd: HWND, command: SHOW_WINDOW_CMD) {
         // BOOL is returned but does not signify whether or not the operation was succesful
         // https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow
@@ -416,6 +440,16 @@ impl WindowsApi {
         Self::set_window_long_ptr_w(hwnd, GWL_STYLE, new_value)
     }
 
+    pub fn update_ex_style(hwnd: HWND, new_value: isize) -> Result<()> {
+        Self::set_window_long_ptr_w(hwnd, GWL_EXSTYLE, new_value)
+    }
+
+    pub fn set_layered_window_attributes(hwnd: HWND, alpha: u8) -> Result<()> {
+        Result::from(WindowsResult::from(unsafe {
+            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA)
+        }))
+    }
+
     pub fn window_text_w(hwnd: HWND) -> Result<String> {
         let mut text: [u16; 512] = [0; 512];
         match WindowsResult::from(unsafe {
@@ -496,7 +530,36 @@ impl WindowsApi {
         Ok(())
     }
 
-    #[allow(dead_code)]
+    pub fn dwm_set_window_attribute<T>(
+        hwnd: HWND,
+        attribute: DWMWINDOWATTRIBUTE,
+        value: &T,
+    ) -> Result<()> {
+        unsafe {
+            DwmSetWindowAttribute(
+                hwnd,
+                u32::try_from(attribute.0)?,
+                (value as *const T).cast(),
+                u32::try_from(std::mem::size_of::<T>())?,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // DWMWA_BORDER_COLOR is only supported from Windows 11 onwards and is missing from the metadata
+    // that the bindings are generated from, so the raw attribute value is used instead
+    pub fn set_window_border_color(hwnd: HWND, color: Option<(u8, u8, u8)>) -> Result<()> {
+        const DWMWA_BORDER_COLOR: DWMWINDOWATTRIBUTE = DWMWINDOWATTRIBUTE(34);
+        const DWMWA_COLOR_DEFAULT: u32 = 0xFFFF_FFFF;
+
+        let colorref = color.map_or(DWMWA_COLOR_DEFAULT, |(r, g, b)| {
+            u32::from(r) | (u32::from(g) << 8) | (u32::from(b) << 16)
+        });
+
+        Self::dwm_set_window_attribute(hwnd, DWMWA_BORDER_COLOR, &colorref)
+    }
+
     pub fn window_rect_with_extended_frame_bounds(hwnd: HWND) -> Result<Rect> {
         let mut rect = RECT::default();
         Self::dwm_get_window_attribute(hwnd, DWMWA_EXTENDED_FRAME_BOUNDS, &mut rect)?;
@@ -526,24 +589,61 @@ impl WindowsApi {
         unsafe { IsIconic(hwnd) }.into()
     }
 
-    pub fn monitor_info_w(hmonitor: HMONITOR) -> Result<MONITORINFO> {
-        let mut monitor_info: MONITORINFO = unsafe { std::mem::zeroed() };
-        monitor_info.cbSize = u32::try_from(std::mem::size_of::<MONITORINFO>())?;
+    pub fn monitor_info_w(hmonitor: HMONITOR) -> Result<MONITORINFOEXW> {
+        let mut ex_info: MONITORINFOEXW = unsafe { std::mem::zeroed() };
+        ex_info.__AnonymousBase_winuser_L13558_C43.cbSize =
+            u32::try_from(std::mem::size_of::<MONITORINFOEXW>())?;
 
         Result::from(WindowsResult::from(unsafe {
-            GetMonitorInfoW(hmonitor, (&mut monitor_info as *mut MONITORINFO).cast())
+            GetMonitorInfoW(hmonitor, (&mut ex_info as *mut MONITORINFOEXW).cast())
         }))?;
 
-        Ok(monitor_info)
+        Ok(ex_info)
+    }
+
+    pub fn dpi_for_monitor(hmonitor: isize) -> Result<u32> {
+        let mut dpi_x = 0;
+        let mut dpi_y = 0;
+
+        unsafe {
+            GetDpiForMonitor(
+                HMONITOR(hmonitor),
+                MDT_EFFECTIVE_DPI,
+                &mut dpi_x,
+                &mut dpi_y,
+            )?;
+        }
+
+        // The effective DPI is always the same on both axes
+        Ok(dpi_x)
+    }
+
+    pub fn monitor_size_from_window(hwnd: HWND) -> Result<Rect> {
+        let hmonitor = HMONITOR(Self::monitor_from_window(hwnd));
+        let ex_info = Self::monitor_info_w(hmonitor)?;
+
+        Ok(ex_info.__AnonymousBase_winuser_L13558_C43.rcMonitor.into())
     }
 
     pub fn monitor(hmonitor: HMONITOR) -> Result<Monitor> {
-        let monitor_info = Self::monitor_info_w(hmonitor)?;
+        let ex_info = Self::monitor_info_w(hmonitor)?;
+        let monitor_info = ex_info.__AnonymousBase_winuser_L13558_C43;
+
+        // This is the device name (eg. \\.\DISPLAY1), which is stable across enumerations
+        let name = String::from_utf16_lossy(&ex_info.szDevice)
+            .trim_end_matches('\0')
+            .to_string();
+
+        // 96 DPI is 100% scaling, which is also assumed if the DPI can't be retrieved
+        let scale_factor = f64::from(Self::dpi_for_monitor(hmonitor.0).unwrap_or(96)) / 96.0;
 
         Ok(monitor::new(
             hmonitor.0,
+            name,
             monitor_info.rcMonitor.into(),
             monitor_info.rcWork.into(),
+            monitor_info.dwFlags & MONITORINFOF_PRIMARY != 0,
+            scale_factor,
         ))
     }
 
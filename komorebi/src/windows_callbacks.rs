@@ -15,6 +15,7 @@ use crate::window::Window;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::windows_api::WindowsApi;
 use crate::winevent_listener::WINEVENT_CALLBACK_CHANNEL;
+use crate::TRACED_HWNDS;
 
 pub extern "system" fn enum_display_monitor(
     hmonitor: HMONITOR,
@@ -74,7 +75,19 @@ pub extern "system" fn win_event_hook(
         Some(event) => event,
     };
 
-    if let Ok(should_manage) = window.should_manage(Option::from(event_type)) {
+    let should_manage = window.should_manage(Option::from(&event_type));
+
+    // Traced events get their own target so that they can be filtered with RUST_LOG=komorebi::trace
+    if TRACED_HWNDS.lock().contains(&window.hwnd) {
+        tracing::info!(
+            target: "komorebi::trace",
+            "traced event: {} (should manage: {:?})",
+            event_type,
+            should_manage
+        );
+    }
+
+    if let Ok(should_manage) = should_manage {
         if should_manage {
             WINEVENT_CALLBACK_CHANNEL
                 .lock()
@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::num::NonZeroUsize;
 
 use color_eyre::eyre::anyhow;
@@ -10,6 +13,8 @@ use getset::MutGetters;
 use getset::Setters;
 use serde::Serialize;
 
+use komorebi_core::scale_to_dpi;
+use komorebi_core::CycleDirection;
 use komorebi_core::Flip;
 use komorebi_core::Layout;
 use komorebi_core::OperationDirection;
@@ -19,10 +24,13 @@ use crate::container::Container;
 use crate::ring::Ring;
 use crate::window::Window;
 use crate::windows_api::WindowsApi;
+use crate::DEFAULT_CONTAINER_PADDING;
+use crate::DEFAULT_LAYOUT;
+use crate::DEFAULT_WORKSPACE_PADDING;
 
 #[derive(Debug, Clone, Serialize, Getters, CopyGetters, MutGetters, Setters)]
 pub struct Workspace {
-    #[getset(set = "pub")]
+    #[getset(get = "pub", set = "pub")]
     name: Option<String>,
     containers: Ring<Container>,
     #[getset(get = "pub", get_mut = "pub", set = "pub")]
@@ -37,22 +45,51 @@ pub struct Workspace {
     maximized_window_restore_idx: Option<usize>,
     #[getset(get = "pub", get_mut = "pub")]
     floating_windows: Vec<Window>,
-    #[getset(get_copy = "pub", set = "pub")]
+    #[getset(get = "pub", get_mut = "pub")]
+    floating_window_restore_positions: Vec<(isize, Rect)>,
+    #[getset(get = "pub", set = "pub")]
     layout: Layout,
     #[getset(get_copy = "pub", set = "pub")]
     layout_flip: Option<Flip>,
+    #[serde(skip_serializing)]
+    #[getset(get = "pub", set = "pub")]
+    soft_maximized_container: Option<String>,
+    #[getset(get = "pub", set = "pub")]
+    previous_layout: Option<Layout>,
     #[getset(get_copy = "pub", set = "pub")]
+    previous_layout_flip: Option<Flip>,
+    #[getset(get_copy = "pub")]
     workspace_padding: Option<i32>,
     #[getset(get_copy = "pub", set = "pub")]
+    workspace_padding_from_edge: Option<Rect>,
+    #[getset(get_copy = "pub", set = "pub")]
     container_padding: Option<i32>,
+    #[getset(get_copy = "pub", set = "pub")]
+    gap: Option<i32>,
+    #[serde(skip_serializing)]
+    pre_gapless_container_padding: Option<i32>,
+    #[serde(skip_serializing)]
+    pre_gapless_workspace_padding: Option<i32>,
+    #[serde(skip_serializing)]
+    pre_gapless_workspace_padding_from_edge: Option<Rect>,
+    #[serde(skip_serializing)]
+    pre_gapless_gap: Option<i32>,
+    #[serde(skip_serializing)]
+    gapless: bool,
     #[serde(skip_serializing)]
     #[getset(get = "pub", set = "pub")]
     latest_layout: Vec<Rect>,
     #[serde(skip_serializing)]
-    #[getset(get = "pub", get_mut = "pub")]
+    #[getset(get = "pub")]
     resize_dimensions: Vec<Option<Rect>>,
     #[getset(get = "pub", set = "pub")]
     tile: bool,
+    #[getset(get_copy = "pub", set = "pub")]
+    scratchpad_visible: bool,
+    #[getset(get_copy = "pub", set = "pub")]
+    mouse_follows_focus: Option<bool>,
+    #[serde(skip_serializing)]
+    windows_set: HashSet<isize>,
 }
 
 impl_ring_elements!(Workspace, Container);
@@ -67,18 +104,68 @@ impl Default for Workspace {
             maximized_window_restore_idx: None,
             monocle_container_restore_idx: None,
             floating_windows: Vec::default(),
-            layout: Layout::BSP,
+            floating_window_restore_positions: Vec::default(),
+            layout: DEFAULT_LAYOUT.lock().clone(),
             layout_flip: None,
-            workspace_padding: Option::from(10),
-            container_padding: Option::from(10),
+            soft_maximized_container: None,
+            previous_layout: None,
+            previous_layout_flip: None,
+            workspace_padding: *DEFAULT_WORKSPACE_PADDING.lock(),
+            workspace_padding_from_edge: None,
+            container_padding: *DEFAULT_CONTAINER_PADDING.lock(),
+            gap: None,
+            pre_gapless_container_padding: None,
+            pre_gapless_workspace_padding: None,
+            pre_gapless_workspace_padding_from_edge: None,
+            pre_gapless_gap: None,
+            gapless: false,
             latest_layout: vec![],
             resize_dimensions: vec![],
             tile: true,
+            scratchpad_visible: false,
+            mouse_follows_focus: None,
+            windows_set: HashSet::new(),
         }
     }
 }
 
 impl Workspace {
+    // Setting a symmetric padding always replaces any padding that was set per edge
+    pub fn set_workspace_padding(&mut self, padding: Option<i32>) -> &mut Self {
+        self.workspace_padding = padding;
+        self.workspace_padding_from_edge = None;
+        self
+    }
+
+    // The previous values are saved exactly as they were, including when they were unset, so that
+    // toggling gapless off again leaves the workspace in the same state as before
+    pub fn toggle_gapless(&mut self) {
+        if self.gapless {
+            self.container_padding = self.pre_gapless_container_padding.take();
+            self.workspace_padding = self.pre_gapless_workspace_padding.take();
+            self.workspace_padding_from_edge = self.pre_gapless_workspace_padding_from_edge.take();
+            self.gap = self.pre_gapless_gap.take();
+        } else {
+            self.pre_gapless_container_padding = self.container_padding.replace(0);
+            self.pre_gapless_workspace_padding = self.workspace_padding.replace(0);
+            self.pre_gapless_workspace_padding_from_edge = self.workspace_padding_from_edge.take();
+            self.pre_gapless_gap = self.gap.replace(0);
+        }
+
+        self.gapless = !self.gapless;
+    }
+
+    // Any code that adds or removes containers without also updating the resize dimensions would
+    // otherwise leave this out of sync and cause a panic the next time the focused container is
+    // resized, so the length is checked and corrected on every access. Methods on Workspace that
+    // insert or remove resize dimensions at a specific index use the field directly, since they
+    // may run after the containers have already been changed
+    pub fn resize_dimensions_mut(&mut self) -> &mut Vec<Option<Rect>> {
+        let container_count = self.containers.len();
+        self.resize_dimensions.resize(container_count, None);
+        &mut self.resize_dimensions
+    }
+
     pub fn hide(&mut self) {
         for container in self.containers_mut() {
             for window in container.windows_mut() {
@@ -101,6 +188,30 @@ impl Workspace {
         }
     }
 
+    // Shows the windows of this workspace where they were last positioned, without it becoming the
+    // focused workspace
+    pub fn show_as_scratchpad(&self) {
+        for container in self.containers() {
+            if let Some(window) = container.focused_window() {
+                window.restore();
+            }
+        }
+
+        if let Some(window) = self.maximized_window() {
+            window.restore();
+        }
+
+        if let Some(container) = self.monocle_container() {
+            if let Some(window) = container.focused_window() {
+                window.restore();
+            }
+        }
+
+        for window in self.floating_windows() {
+            window.restore();
+        }
+    }
+
     pub fn restore(&mut self) -> Result<()> {
         let idx = self.focused_container_idx();
         let mut to_focus = None;
@@ -124,8 +235,15 @@ impl Workspace {
             }
         }
 
+        // Floating windows hidden behind a monocle container should stay hidden until it is removed
         for window in self.floating_windows() {
-            window.restore();
+            if !self
+                .floating_window_restore_positions()
+                .iter()
+                .any(|(hwnd, _)| *hwnd == window.hwnd)
+            {
+                window.restore();
+            }
         }
 
         // Do this here to make sure that an error doesn't stop the restoration of other windows
@@ -139,9 +257,16 @@ impl Workspace {
         Ok(())
     }
 
-    pub fn update(&mut self, work_area: &Rect) -> Result<()> {
+    pub fn update(&mut self, work_area: &Rect, scale_factor: f64) -> Result<()> {
         let mut adjusted_work_area = *work_area;
-        adjusted_work_area.add_padding(self.workspace_padding());
+        if let Some(padding) = self.workspace_padding_from_edge() {
+            adjusted_work_area.add_edge_padding(&padding.scale(scale_factor));
+        } else {
+            adjusted_work_area.add_padding(
+                self.workspace_padding()
+                    .map(|padding| scale_to_dpi(padding, scale_factor)),
+            );
+        }
 
         self.enforce_resize_constraints();
 
@@ -153,20 +278,52 @@ impl Workspace {
             } else if let Some(window) = self.maximized_window_mut() {
                 window.maximize();
             } else if !self.containers().is_empty() {
-                let layouts = self.layout().calculate(
+                let mut layouts = self.layout().calculate(
                     &adjusted_work_area,
                     NonZeroUsize::new(self.containers().len()).context(
                         "there must be at least one container to calculate a workspace layout",
                     )?,
-                    self.container_padding(),
+                    self.container_padding()
+                        .map(|padding| scale_to_dpi(padding, scale_factor)),
+                    scale_to_dpi(self.gap().unwrap_or_default(), scale_factor),
                     self.layout_flip(),
                     self.resize_dimensions(),
-                );
+                )?;
+
+                let container_padding =
+                    scale_to_dpi(self.container_padding().unwrap_or_default(), scale_factor);
+                for (container, layout) in self.containers().iter().zip(layouts.iter_mut()) {
+                    // The layout has already been padded with the workspace's container padding,
+                    // so only the difference needs to be applied for windows with their own rule
+                    if let Some(padding) = container
+                        .focused_window()
+                        .and_then(|window| window.container_padding_rule())
+                    {
+                        layout.add_padding(Option::from(
+                            scale_to_dpi(padding, scale_factor) - container_padding,
+                        ));
+                    }
+
+                    if let Some((width, height)) = container.aspect_ratio() {
+                        layout.constrain_to_aspect_ratio(width, height);
+                    }
+                }
+
+                // A soft maximized container keeps its place in the layout, but is drawn on top of
+                // the other containers using the whole work area
+                let soft_maximized_idx = self
+                    .soft_maximized_container()
+                    .as_ref()
+                    .and_then(|id| self.containers().iter().position(|c| c.id() == id));
+
+                if let Some(layout) = soft_maximized_idx.and_then(|idx| layouts.get_mut(idx)) {
+                    *layout = adjusted_work_area;
+                }
 
                 let windows = self.visible_windows_mut();
                 for (i, window) in windows.into_iter().enumerate() {
                     if let (Some(window), Some(layout)) = (window, layouts.get(i)) {
-                        window.set_position(layout, false)?;
+                        window.set_position(layout, soft_maximized_idx == Option::from(i))?;
                     }
                 }
 
@@ -174,11 +331,12 @@ impl Workspace {
             }
         }
 
+        self.sync_windows_set();
+
         // Always make sure that the length of the resize dimensions vec is the same as the
         // number of layouts / containers. This should never actually truncate as the remove_window
         // function takes care of cleaning up resize dimensions when destroying empty containers
-        let container_count = self.containers().len();
-        self.resize_dimensions_mut().resize(container_count, None);
+        self.resize_dimensions_mut();
 
         Ok(())
     }
@@ -220,6 +378,8 @@ impl Workspace {
         self.containers_mut()
             .retain(|c| !container_ids.contains(c.id()));
 
+        self.sync_windows_set();
+
         Ok((hwnds.len() + floating_hwnds.len(), container_ids.len()))
     }
 
@@ -227,6 +387,30 @@ impl Workspace {
         self.containers().get(self.container_idx_for_window(hwnd)?)
     }
 
+    pub fn container_idx_for_exe(&self, exe: &str) -> Option<usize> {
+        self.containers().iter().position(|container| {
+            container
+                .windows()
+                .iter()
+                .any(|window| window.exe().ok().as_deref() == Some(exe))
+        })
+    }
+
+    pub fn add_window_to_container_at_idx(&mut self, idx: usize, window: Window) -> Result<()> {
+        let container = self
+            .containers_mut()
+            .get_mut(idx)
+            .ok_or_else(|| anyhow!("there is no container at index {}", idx))?;
+
+        container.add_window(window);
+        container.load_focused_window();
+
+        self.focus_container(idx);
+        self.sync_windows_set();
+
+        Ok(())
+    }
+
     pub fn focus_container_by_window(&mut self, hwnd: isize) -> Result<()> {
         let container_idx = self
             .container_idx_for_window(hwnd)
@@ -264,31 +448,37 @@ impl Workspace {
     }
 
     pub fn contains_window(&self, hwnd: isize) -> bool {
-        for container in self.containers() {
-            if container.contains_window(hwnd) {
-                return true;
-            }
-        }
+        self.windows_set.contains(&hwnd)
+    }
 
-        if let Some(window) = self.maximized_window() {
-            if hwnd == window.hwnd {
-                return true;
-            }
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+            && self.floating_windows().is_empty()
+            && self.monocle_container().is_none()
+            && self.maximized_window().is_none()
+    }
+
+    // Rebuilt whenever a window is added to or removed from this workspace, so that
+    // contains_window doesn't have to go through every container each time it is called. Windows
+    // moving between containers, the floating layer, monocle or maximized don't change the set
+    pub fn sync_windows_set(&mut self) {
+        let mut windows_set = HashSet::new();
+
+        for container in self.containers() {
+            windows_set.extend(container.windows().iter().map(|w| w.hwnd));
         }
 
         if let Some(container) = self.monocle_container() {
-            if container.contains_window(hwnd) {
-                return true;
-            }
+            windows_set.extend(container.windows().iter().map(|w| w.hwnd));
         }
 
-        for window in self.floating_windows() {
-            if hwnd == window.hwnd {
-                return true;
-            }
+        if let Some(window) = self.maximized_window() {
+            windows_set.insert(window.hwnd);
         }
 
-        false
+        windows_set.extend(self.floating_windows().iter().map(|w| w.hwnd));
+
+        self.windows_set = windows_set;
     }
 
     pub fn promote_container(&mut self) -> Result<()> {
@@ -296,19 +486,95 @@ impl Workspace {
             .remove_focused_container()
             .ok_or_else(|| anyhow!("there is no container"))?;
         self.containers_mut().push_front(container);
-        self.resize_dimensions_mut().insert(0, None);
+        self.resize_dimensions.insert(0, None);
         self.focus_container(0);
 
         Ok(())
     }
 
+    pub fn sort_containers_by_key(&mut self, key: fn(Window) -> Result<String>) {
+        let focused_id = self.focused_container().map(|c| c.id().clone());
+
+        let mut keyed: Vec<(String, Container)> = std::mem::take(self.containers_mut())
+            .into_iter()
+            .map(|container| {
+                let sort_key = container
+                    .focused_window()
+                    .and_then(|window| key(*window).ok())
+                    .unwrap_or_default()
+                    .to_lowercase();
+
+                (sort_key, container)
+            })
+            .collect();
+
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.containers_mut()
+            .extend(keyed.into_iter().map(|(_, container)| container));
+
+        self.focus_container_by_id(focused_id);
+    }
+
+    pub fn rotate_containers(&mut self, direction: CycleDirection) {
+        if self.containers().is_empty() {
+            return;
+        }
+
+        let focused_id = self.focused_container().map(|c| c.id().clone());
+
+        match direction {
+            CycleDirection::Previous => self.containers_mut().rotate_left(1),
+            CycleDirection::Next => self.containers_mut().rotate_right(1),
+        }
+
+        self.focus_container_by_id(focused_id);
+    }
+
+    // A fixed seed is used so that shuffling the same containers in the same order always gives
+    // the same result, which makes layouts reproducible
+    pub fn shuffle_containers(&mut self) -> Result<()> {
+        let focused_id = self.focused_container().map(|c| c.id().clone());
+
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let containers = self.containers_mut();
+        for i in (1..containers.len()).rev() {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            let j = usize::try_from(state % u64::try_from(i + 1)?)?;
+            containers.swap(i, j);
+        }
+
+        self.focus_container_by_id(focused_id);
+
+        Ok(())
+    }
+
+    fn focus_container_by_id(&mut self, id: Option<String>) {
+        if let Some(idx) = id.and_then(|id| self.containers().iter().position(|c| c.id() == &id)) {
+            self.focus_container(idx);
+        }
+    }
+
     pub fn add_container(&mut self, container: Container) {
         self.containers_mut().push_back(container);
         self.focus_last_container();
+        self.sync_windows_set();
+    }
+
+    pub fn add_floating_window(&mut self, window: Window) {
+        self.floating_windows_mut().push(window);
+        self.sync_windows_set();
     }
 
     fn remove_container_by_idx(&mut self, idx: usize) -> Option<Container> {
-        self.resize_dimensions_mut().remove(idx);
+        if idx < self.resize_dimensions.len() {
+            self.resize_dimensions.remove(idx);
+        }
+
         self.containers_mut().remove(idx)
     }
 
@@ -323,9 +589,16 @@ impl Workspace {
         idx
     }
 
+    // Monocle and maximized windows aren't in any of the containers, so remove_window can't find them
+    pub fn can_remove_window(&self, hwnd: isize) -> bool {
+        self.floating_windows().iter().any(|w| w.hwnd == hwnd)
+            || self.container_idx_for_window(hwnd).is_some()
+    }
+
     pub fn remove_window(&mut self, hwnd: isize) -> Result<()> {
         if self.floating_windows().iter().any(|w| w.hwnd == hwnd) {
             self.floating_windows_mut().retain(|w| w.hwnd != hwnd);
+            self.windows_set.remove(&hwnd);
             return Ok(());
         }
 
@@ -355,11 +628,12 @@ impl Workspace {
 
             // Whenever a container is empty, we need to remove any resize dimensions for it too
             if self.resize_dimensions().get(container_idx).is_some() {
-                self.resize_dimensions_mut().remove(container_idx);
+                self.resize_dimensions.remove(container_idx);
             }
         }
 
         self.focus_previous_container();
+        self.windows_set.remove(&hwnd);
 
         Ok(())
     }
@@ -368,6 +642,7 @@ impl Workspace {
         let focused_idx = self.focused_container_idx();
         let container = self.remove_container_by_idx(focused_idx);
         self.focus_previous_container();
+        self.sync_windows_set();
 
         container
     }
@@ -383,6 +658,7 @@ impl Workspace {
                 self.layout(),
                 self.layout_flip(),
                 self.containers.focused_idx(),
+                self.containers().len(),
             ))
         } else {
             None
@@ -403,7 +679,7 @@ impl Workspace {
         // This is a little messy
         let adjusted_target_container_index = if container.windows().is_empty() {
             self.containers_mut().remove(focused_idx);
-            self.resize_dimensions_mut().remove(focused_idx);
+            self.resize_dimensions.remove(focused_idx);
 
             if focused_idx < target_container_idx {
                 target_container_idx - 1
@@ -443,7 +719,7 @@ impl Workspace {
 
         if container.windows().is_empty() {
             self.containers_mut().remove(focused_container_idx);
-            self.resize_dimensions_mut().remove(focused_container_idx);
+            self.resize_dimensions.remove(focused_container_idx);
         } else {
             container.load_focused_window();
         }
@@ -455,20 +731,131 @@ impl Workspace {
         Ok(())
     }
 
+    pub fn split_focused_container(&mut self, at: usize) -> Result<()> {
+        let focused_idx = self.focused_container_idx();
+        let window_count = self
+            .focused_container()
+            .ok_or_else(|| anyhow!("there is no container"))?
+            .windows()
+            .len();
+
+        if at == 0 || at >= window_count {
+            return Err(anyhow!(
+                "a container with {} windows cannot be split at index {}",
+                window_count,
+                at
+            ));
+        }
+
+        let container = self
+            .containers_mut()
+            .remove(focused_idx)
+            .ok_or_else(|| anyhow!("there is no container"))?;
+
+        let focused_window_idx = container.focused_window_idx();
+        let (mut left, mut right) = container.split(at);
+        left.load_focused_window();
+        right.load_focused_window();
+
+        self.containers_mut().insert(focused_idx, right);
+        self.containers_mut().insert(focused_idx, left);
+        if focused_idx + 1 > self.resize_dimensions().len() {
+            self.resize_dimensions.push(None);
+        } else {
+            self.resize_dimensions.insert(focused_idx + 1, None);
+        }
+
+        if focused_window_idx >= at {
+            self.focus_container(focused_idx + 1);
+        }
+
+        Ok(())
+    }
+
+    pub fn merge_focused_container_with(&mut self, target_idx: usize) -> Result<()> {
+        let focused_idx = self.focused_container_idx();
+        if target_idx == focused_idx || target_idx >= self.containers().len() {
+            return Err(anyhow!(
+                "there is no other container at index {}",
+                target_idx
+            ));
+        }
+
+        let target = self
+            .remove_container_by_idx(target_idx)
+            .ok_or_else(|| anyhow!("there is no container"))?;
+
+        let adjusted_focused_idx = if target_idx < focused_idx {
+            focused_idx - 1
+        } else {
+            focused_idx
+        };
+
+        let focused = self
+            .containers_mut()
+            .remove(adjusted_focused_idx)
+            .ok_or_else(|| anyhow!("there is no container"))?;
+
+        let mut container = focused.merge(target);
+        container.load_focused_window();
+
+        self.containers_mut()
+            .insert(adjusted_focused_idx, container);
+        self.focus_container(adjusted_focused_idx);
+
+        Ok(())
+    }
+
     pub fn new_container_for_floating_window(&mut self) -> Result<()> {
         let focused_idx = self.focused_container_idx();
+        self.new_container_for_floating_window_at_idx(focused_idx)
+    }
+
+    pub fn new_container_for_floating_window_at_idx(&mut self, idx: usize) -> Result<()> {
+        if idx > self.containers().len() {
+            return Err(anyhow!("there is no container at index {}", idx));
+        }
+
         let window = self
             .remove_focused_floating_window()
             .ok_or_else(|| anyhow!("there is no floating window"))?;
 
         let mut container = Container::default();
         container.add_window(window);
-        self.containers_mut().insert(focused_idx, container);
-        self.resize_dimensions_mut().insert(focused_idx, None);
+        self.containers_mut().insert(idx, container);
+        self.sync_windows_set();
+
+        if idx > self.resize_dimensions().len() {
+            self.resize_dimensions.push(None);
+        } else {
+            self.resize_dimensions.insert(idx, None);
+        }
 
         Ok(())
     }
 
+    pub fn new_containers_for_floating_windows(&mut self) {
+        let floating_windows = std::mem::take(self.floating_windows_mut());
+
+        for window in floating_windows {
+            self.new_container_for_window(window);
+        }
+    }
+
+    pub fn new_floating_windows_for_containers(&mut self) {
+        let containers = std::mem::take(self.containers_mut());
+
+        for mut container in containers {
+            while let Some(window) = container.remove_window_by_idx(0) {
+                self.floating_windows_mut().push(window);
+            }
+        }
+
+        self.resize_dimensions.clear();
+        self.focus_container(0);
+        self.sync_windows_set();
+    }
+
     pub fn new_container_for_window(&mut self, window: Window) {
         let next_idx = if self.containers().is_empty() {
             0
@@ -486,12 +873,13 @@ impl Workspace {
         }
 
         if next_idx > self.resize_dimensions().len() {
-            self.resize_dimensions_mut().push(None);
+            self.resize_dimensions.push(None);
         } else {
-            self.resize_dimensions_mut().insert(next_idx, None);
+            self.resize_dimensions.insert(next_idx, None);
         }
 
         self.focus_container(next_idx);
+        self.sync_windows_set();
     }
 
     pub fn new_floating_window(&mut self) -> Result<()> {
@@ -507,7 +895,7 @@ impl Workspace {
 
         if container.windows().is_empty() {
             self.containers_mut().remove(focused_idx);
-            self.resize_dimensions_mut().remove(focused_idx);
+            self.resize_dimensions.remove(focused_idx);
         } else {
             container.load_focused_window();
         }
@@ -543,6 +931,84 @@ impl Workspace {
         }
     }
 
+    pub fn change_layout(&mut self, layout: Layout) {
+        self.set_previous_layout(Option::from(self.layout().clone()));
+        self.set_layout(layout);
+    }
+
+    pub fn undo_layout_change(&mut self) -> Result<()> {
+        let previous_layout = self
+            .previous_layout()
+            .clone()
+            .ok_or_else(|| anyhow!("there is no layout change to undo"))?;
+
+        // Swapping rather than clearing means that undoing twice redoes the change
+        self.change_layout(previous_layout);
+
+        Ok(())
+    }
+
+    pub fn undo_flip_layout(&mut self) {
+        // No flip is a valid previous state, so there is nothing to check here
+        let previous_layout_flip = self.previous_layout_flip();
+        self.set_previous_layout_flip(self.layout_flip());
+        self.set_layout_flip(previous_layout_flip);
+    }
+
+    pub fn flip_layout(&mut self, layout_flip: Flip) {
+        self.set_previous_layout_flip(self.layout_flip());
+
+        #[allow(clippy::match_same_arms)]
+        match self.layout_flip() {
+            None => {
+                self.set_layout_flip(Option::from(layout_flip));
+            }
+            Some(current_layout_flip) => {
+                match current_layout_flip {
+                    Flip::Horizontal => match layout_flip {
+                        Flip::Horizontal => self.set_layout_flip(None),
+                        Flip::Vertical => {
+                            self.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
+                        }
+                        Flip::HorizontalAndVertical => {
+                            self.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
+                        }
+                    },
+                    Flip::Vertical => match layout_flip {
+                        Flip::Horizontal => {
+                            self.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
+                        }
+                        Flip::Vertical => self.set_layout_flip(None),
+                        Flip::HorizontalAndVertical => {
+                            self.set_layout_flip(Option::from(Flip::HorizontalAndVertical))
+                        }
+                    },
+                    Flip::HorizontalAndVertical => match layout_flip {
+                        Flip::Horizontal => self.set_layout_flip(Option::from(Flip::Vertical)),
+                        Flip::Vertical => self.set_layout_flip(Option::from(Flip::Horizontal)),
+                        Flip::HorizontalAndVertical => self.set_layout_flip(None),
+                    },
+                };
+            }
+        }
+    }
+
+    pub fn toggle_soft_maximize(&mut self) -> Result<()> {
+        let id = self
+            .focused_container()
+            .ok_or_else(|| anyhow!("there is no container"))?
+            .id()
+            .clone();
+
+        if self.soft_maximized_container.as_ref() == Option::from(&id) {
+            self.soft_maximized_container = None;
+        } else {
+            self.soft_maximized_container = Option::from(id);
+        }
+
+        Ok(())
+    }
+
     pub fn new_monocle_container(&mut self) -> Result<()> {
         let focused_idx = self.focused_container_idx();
         let container = self
@@ -594,6 +1060,30 @@ impl Workspace {
         Ok(())
     }
 
+    pub fn hide_floating_windows(&mut self) -> Result<()> {
+        let mut positions = vec![];
+        for window in self.floating_windows() {
+            positions.push((window.hwnd, WindowsApi::window_rect(window.hwnd())?));
+            window.hide();
+        }
+
+        *self.floating_window_restore_positions_mut() = positions;
+
+        Ok(())
+    }
+
+    pub fn restore_floating_windows(&mut self) -> Result<()> {
+        let positions = std::mem::take(self.floating_window_restore_positions_mut());
+        for (hwnd, rect) in positions {
+            if let Some(window) = self.floating_windows().iter().find(|w| w.hwnd == hwnd) {
+                window.restore();
+                WindowsApi::position_window(window.hwnd(), &rect, false)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn new_maximized_window(&mut self) -> Result<()> {
         let focused_idx = self.focused_container_idx();
 
@@ -607,7 +1097,7 @@ impl Workspace {
 
         if container.windows().is_empty() {
             self.containers_mut().remove(focused_idx);
-            self.resize_dimensions_mut().remove(focused_idx);
+            self.resize_dimensions.remove(focused_idx);
         } else {
             container.load_focused_window();
         }
@@ -682,7 +1172,9 @@ impl Workspace {
             None => None,
             Some(idx) => {
                 if self.floating_windows.get(idx).is_some() {
-                    Option::from(self.floating_windows_mut().remove(idx))
+                    let window = self.floating_windows_mut().remove(idx);
+                    self.windows_set.remove(&window.hwnd);
+                    Option::from(window)
                 } else {
                     None
                 }
@@ -699,6 +1191,19 @@ impl Workspace {
         vec
     }
 
+    // Matches the rects calculated the last time this workspace was tiled to the focused window of
+    // the container at the same index
+    pub fn visible_window_rects(&self) -> HashMap<isize, Rect> {
+        let mut rects = HashMap::new();
+        for (i, window) in self.visible_windows().into_iter().enumerate() {
+            if let (Some(window), Some(rect)) = (window, self.latest_layout().get(i)) {
+                rects.insert(window.hwnd, *rect);
+            }
+        }
+
+        rects
+    }
+
     pub fn visible_windows_mut(&mut self) -> Vec<Option<&mut Window>> {
         let mut vec = vec![];
         for container in self.containers_mut() {
@@ -717,6 +1222,6 @@ impl Workspace {
     }
 
     fn focus_last_container(&mut self) {
-        self.focus_container(self.containers().len() - 1);
+        self.focus_container(self.containers.len() - 1);
     }
 }
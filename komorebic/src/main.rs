@@ -6,14 +6,27 @@ use std::fs::OpenOptions;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::ErrorKind;
+use std::io::Read;
 use std::io::Write;
+use std::net::Shutdown;
 use std::path::PathBuf;
 use std::process::Command;
 use std::stringify;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use clap::AppSettings;
 use clap::ArgEnum;
 use clap::Clap;
+use clap::IntoApp;
+use clap_generate::generate;
+use clap_generate::generators::Bash;
+use clap_generate::generators::Elvish;
+use clap_generate::generators::Fish;
+use clap_generate::generators::PowerShell;
+use clap_generate::generators::Zsh;
+use color_eyre::eyre::anyhow;
 use color_eyre::eyre::ContextCompat;
 use color_eyre::Result;
 use fs_tail::TailedFile;
@@ -28,13 +41,20 @@ use bindings::Windows::Win32::UI::WindowsAndMessaging::SHOW_WINDOW_CMD;
 use bindings::Windows::Win32::UI::WindowsAndMessaging::SW_RESTORE;
 use derive_ahk::AhkFunction;
 use derive_ahk::AhkLibrary;
+use derive_ahk::PowerShellFunction;
+use derive_ahk::PowerShellLibrary;
 use komorebi_core::ApplicationIdentifier;
 use komorebi_core::CycleDirection;
 use komorebi_core::Flip;
 use komorebi_core::Layout;
+use komorebi_core::MonitorWorkspaceMapping;
 use komorebi_core::OperationDirection;
+use komorebi_core::ProcessCommandResponse;
+use komorebi_core::Rect;
 use komorebi_core::Sizing;
 use komorebi_core::SocketMessage;
+use komorebi_core::WindowManagerEventType;
+use komorebi_core::WorkspaceRuleCondition;
 
 trait AhkLibrary {
     fn generate_ahk_library() -> String;
@@ -44,6 +64,10 @@ trait AhkFunction {
     fn generate_ahk_function() -> String;
 }
 
+trait PowerShellFunction {
+    fn generate_powershell_function() -> String;
+}
+
 #[derive(ArgEnum)]
 enum BooleanState {
     Enable,
@@ -64,7 +88,7 @@ macro_rules! gen_enum_subcommand_args {
     ( $( $name:ident: $element:ty ),+ ) => {
         $(
             paste! {
-                #[derive(clap::Clap, derive_ahk::AhkFunction)]
+                #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
                 pub struct $name {
                     #[clap(arg_enum)]
                     [<$element:snake>]: $element
@@ -78,18 +102,32 @@ gen_enum_subcommand_args! {
     Focus: OperationDirection,
     Move: OperationDirection,
     Stack: OperationDirection,
+    MergeContainers: OperationDirection,
     CycleStack: CycleDirection,
+    FocusFloatingWindow: CycleDirection,
+    CycleMonocle: CycleDirection,
+    CycleContainerAcrossWorkspaces: CycleDirection,
+    RotateContainers: CycleDirection,
+    MoveToNextNonEmptyWorkspace: CycleDirection,
+    FocusMonitorInDirection: OperationDirection,
+    FocusCycleMonitor: CycleDirection,
     FlipLayout: Flip,
-    ChangeLayout: Layout,
     WatchConfiguration: BooleanState,
-    FocusFollowsMouse: BooleanState
+    FocusFollowsMouse: BooleanState,
+    ToggleAutoFullscreenPause: BooleanState,
+    ToggleMonocleIncludeFloating: BooleanState,
+    ToggleAutoManage: BooleanState,
+    ToggleAutoStackByExe: BooleanState,
+    ToggleVirtualDesktopIntegration: BooleanState,
+    ToggleDynamicWorkspaceNaming: BooleanState,
+    ToggleDpiAwarePadding: BooleanState
 }
 
 macro_rules! gen_target_subcommand_args {
     // SubCommand Pattern
     ( $( $name:ident ),+ ) => {
         $(
-            #[derive(clap::Clap, derive_ahk::AhkFunction)]
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
             pub struct $name {
                 /// Target index (zero-indexed)
                 target: usize,
@@ -102,7 +140,9 @@ gen_target_subcommand_args! {
     MoveToMonitor,
     MoveToWorkspace,
     FocusMonitor,
-    FocusWorkspace
+    FocusWorkspace,
+    CenterFloatingWindowOnMonitor,
+    MoveFloatingWindowToTile
 }
 
 // Thanks to @danielhenrymantilla for showing me how to use cfg_attr with an optional argument like
@@ -113,7 +153,7 @@ macro_rules! gen_workspace_subcommand_args {
     ( $( $name:ident: $(#[enum] $(@$arg_enum:tt)?)? $value:ty ),+ ) => (
         paste! {
             $(
-                #[derive(clap::Clap, derive_ahk::AhkFunction)]
+                #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
                 pub struct [<Workspace $name>] {
                     /// Monitor index (zero-indexed)
                     monitor: usize,
@@ -135,11 +175,12 @@ macro_rules! gen_workspace_subcommand_args {
 
 gen_workspace_subcommand_args! {
     Name: String,
-    Layout: #[enum] Layout,
-    Tiling: #[enum] BooleanState
+    LayoutFlip: #[enum] Flip,
+    Tiling: #[enum] BooleanState,
+    MouseFollowsFocus: #[enum] BooleanState
 }
 
-#[derive(Clap, AhkFunction)]
+#[derive(Clap, AhkFunction, PowerShellFunction)]
 struct Resize {
     #[clap(arg_enum)]
     edge: OperationDirection,
@@ -147,7 +188,71 @@ struct Resize {
     sizing: Sizing,
 }
 
-#[derive(Clap, AhkFunction)]
+#[derive(Clap)]
+struct ResizeWindowAbsolute {
+    /// Width of the focused container in pixels
+    #[clap(long)]
+    width: Option<i32>,
+    /// Height of the focused container in pixels
+    #[clap(long)]
+    height: Option<i32>,
+}
+
+impl AhkFunction for ResizeWindowAbsolute {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+ResizeWindowAbsolute(width := "", height := "") {
+    arguments := ""
+    if (width != "")
+        arguments .= " --width " . width
+    if (height != "")
+        arguments .= " --height " . height
+
+    Run, komorebic.exe resize-window-absolute%arguments%, , Hide
+}"#,
+        )
+    }
+}
+
+impl PowerShellFunction for ResizeWindowAbsolute {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r#"
+function Invoke-KomorebicResizeWindowAbsolute($width, $height) {
+    $arguments = @()
+    if ($null -ne $width) {
+        $arguments += @("--width", $width)
+    }
+
+    if ($null -ne $height) {
+        $arguments += @("--height", $height)
+    }
+
+    komorebic.exe resize-window-absolute @arguments
+}"#,
+        )
+    }
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetMinimumContainerSize {
+    /// Minimum width of a container in pixels
+    width: u32,
+    /// Minimum height of a container in pixels
+    height: u32,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct LockContainerAspectRatio {
+    /// Width component of the aspect ratio (eg. 16 for 16:9)
+    width: u32,
+    /// Height component of the aspect ratio (eg. 9 for 16:9)
+    height: u32,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+#[ahk(async)]
 struct EnsureWorkspaces {
     /// Monitor index (zero-indexed)
     monitor: usize,
@@ -155,11 +260,490 @@ struct EnsureWorkspaces {
     workspace_count: usize,
 }
 
+#[derive(Clap, PowerShellFunction)]
+struct ChangeLayout {
+    #[clap(arg_enum)]
+    layout: Layout,
+    /// Path to the executable that calculates the layout (custom layout only), or the number of
+    /// containers in the primary area (composite layout only)
+    path: Option<String>,
+    /// Layout of the primary area on the left half of the workspace (composite layout only)
+    #[clap(arg_enum)]
+    primary: Option<Layout>,
+    /// Layout of the secondary area on the right half of the workspace (composite layout only)
+    #[clap(arg_enum)]
+    secondary: Option<Layout>,
+}
+
+// The path and the composite layout areas are only needed for some layouts, so they are optional in
+// the generated AHK function to avoid breaking existing configurations
+impl AhkFunction for ChangeLayout {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+ChangeLayout(layout, path := "", primary := "", secondary := "") {
+    Run, komorebic.exe change-layout %layout% %path% %primary% %secondary%, , Hide
+}"#,
+        )
+    }
+}
+
+#[derive(Clap, PowerShellFunction)]
+struct WorkspaceLayout {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+    #[clap(arg_enum)]
+    value: Layout,
+    /// Path to the executable that calculates the layout (custom layout only)
+    path: Option<String>,
+}
+
+impl AhkFunction for WorkspaceLayout {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+WorkspaceLayout(monitor, workspace, value, path := "") {
+    Run, komorebic.exe workspace-layout %monitor% %workspace% %value% %path%, , Hide
+}"#,
+        )
+    }
+}
+
+#[derive(Clap, PowerShellFunction)]
+struct SetWorkspaceDefaultLayout {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    #[clap(arg_enum)]
+    value: Layout,
+    /// Path to the executable that calculates the layout (custom layout only)
+    path: Option<String>,
+}
+
+impl AhkFunction for SetWorkspaceDefaultLayout {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+SetWorkspaceDefaultLayout(monitor, value, path := "") {
+    Run, komorebic.exe set-workspace-default-layout %monitor% %value% %path%, , Hide
+}"#,
+        )
+    }
+}
+
+#[derive(Clap, PowerShellFunction)]
+struct SetGlobalDefaultLayout {
+    #[clap(arg_enum)]
+    value: Layout,
+    /// Path to the executable that calculates the layout (custom layout only)
+    path: Option<String>,
+}
+
+impl AhkFunction for SetGlobalDefaultLayout {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+SetGlobalDefaultLayout(value, path := "") {
+    Run, komorebic.exe set-global-default-layout %value% %path%, , Hide
+}"#,
+        )
+    }
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetFocusedMonitorWorkspace {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+}
+
+#[derive(Clap)]
+struct Start {
+    /// Block until komorebi is ready to receive commands
+    #[clap(long = "await")]
+    await_ready: bool,
+    /// Maximum number of seconds to wait for komorebi to become ready
+    #[clap(long, default_value = "5")]
+    timeout: u64,
+    /// Path to a configuration file to load instead of the one in the home directory
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Restore the layout saved by `komorebic stop --save-state` without asking
+    #[clap(long)]
+    restore_state: bool,
+    /// Also write structured logs to ~/komorebi.json.log for `komorebic log --json`
+    #[clap(long)]
+    json_log: bool,
+}
+
+// The flags are only useful when calling komorebic from a script, so the generated AHK function
+// keeps the same signature as before
+impl AhkFunction for Start {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r"
+Start() {
+    Run, komorebic.exe start, , Hide
+}",
+        )
+    }
+}
+
+impl PowerShellFunction for Start {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r#"
+function Invoke-KomorebicStart($await_ready, $timeout = 5, $config, $json_log) {
+    $arguments = @()
+    if ($config) {
+        $arguments += @("--config", $config)
+    }
+
+    if ($json_log) {
+        $arguments += @("--json-log")
+    }
+
+    if ($await_ready) {
+        $arguments += @("--await", "--timeout", $timeout)
+    }
+
+    komorebic.exe start @arguments
+}"#,
+        )
+    }
+}
+
+#[derive(Clap)]
+struct Stop {
+    /// Save the current layout so that it can be restored with `komorebic restore-state`
+    #[clap(long)]
+    save_state: bool,
+}
+
+impl AhkFunction for Stop {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r"
+Stop() {
+    Run, komorebic.exe stop, , Hide
+}",
+        )
+    }
+}
+
+impl PowerShellFunction for Stop {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r"
+function Invoke-KomorebicStop($save_state) {
+    if ($save_state) {
+        komorebic.exe stop --save-state
+    } else {
+        komorebic.exe stop
+    }
+}",
+        )
+    }
+}
+
+#[derive(Clap)]
+struct TogglePauseMonitor {
+    /// Monitor index (zero-indexed), defaults to the focused monitor
+    #[clap(long)]
+    monitor: Option<usize>,
+}
+
+impl AhkFunction for TogglePauseMonitor {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+TogglePauseMonitor(monitor := "") {
+    if (monitor = "") {
+        Run, komorebic.exe toggle-pause-monitor, , Hide
+    } else {
+        Run, komorebic.exe toggle-pause-monitor --monitor %monitor%, , Hide
+    }
+}"#,
+        )
+    }
+}
+
+impl PowerShellFunction for TogglePauseMonitor {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r#"
+function Invoke-KomorebicTogglePauseMonitor($monitor) {
+    $arguments = @()
+    if ($null -ne $monitor) {
+        $arguments += @("--monitor", $monitor)
+    }
+
+    komorebic.exe toggle-pause-monitor @arguments
+}"#,
+        )
+    }
+}
+
+#[derive(Clap)]
+struct NewWorkspace {
+    /// Name to give the new workspace
+    #[clap(long)]
+    name: Option<String>,
+}
+
+impl AhkFunction for NewWorkspace {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r#"
+NewWorkspace(name := "") {
+    if (name = "") {
+        Run, komorebic.exe new-workspace, , Hide
+    } else {
+        Run, komorebic.exe new-workspace --name "%name%", , Hide
+    }
+}"#,
+        )
+    }
+}
+
+impl PowerShellFunction for NewWorkspace {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r#"
+function Invoke-KomorebicNewWorkspace($name) {
+    $arguments = @()
+    if ($null -ne $name) {
+        $arguments += @("--name", $name)
+    }
+
+    komorebic.exe new-workspace @arguments
+}"#,
+        )
+    }
+}
+
+#[derive(Clap)]
+struct Log {
+    /// Tail the log file without ANSI color codes
+    #[clap(long)]
+    no_color: bool,
+    /// Tail the structured log file written by `komorebic start --json-log`, printing the timestamp and level of each line
+    #[clap(long, conflicts_with = "no-color")]
+    json: bool,
+}
+
+impl AhkFunction for Log {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r"
+Log() {
+    Run, komorebic.exe log, , Hide
+}",
+        )
+    }
+}
+
+impl PowerShellFunction for Log {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r"
+function Invoke-KomorebicLog($no_color, $json) {
+    if ($json) {
+        komorebic.exe log --json
+    } elseif ($no_color) {
+        komorebic.exe log --no-color
+    } else {
+        komorebic.exe log
+    }
+}",
+        )
+    }
+}
+
+#[derive(Clap)]
+struct Ping {
+    /// Maximum number of milliseconds to wait for komorebi to respond
+    #[clap(long, default_value = "1000")]
+    timeout: u64,
+}
+
+impl AhkFunction for Ping {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r"
+Ping() {
+    Run, komorebic.exe ping, , Hide
+}",
+        )
+    }
+}
+
+impl PowerShellFunction for Ping {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r"
+function Invoke-KomorebicPing($timeout = 1000) {
+    komorebic.exe ping --timeout $timeout
+}",
+        )
+    }
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct MoveWindowInContainer {
+    /// Position in the container's stack to move the focused window to (zero-indexed)
+    position: usize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SplitContainer {
+    /// Index of the first window in the focused stack to move into the new container (zero-indexed)
+    at: usize,
+}
+
+macro_rules! gen_named_scratchpad_subcommand_args {
+    // SubCommand Pattern
+    ( $( $name:ident ),+ ) => {
+        $(
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
+            pub struct $name {
+                /// Name of the scratchpad
+                name: String,
+            }
+        )+
+    };
+}
+
+gen_named_scratchpad_subcommand_args! {
+    SendToNamedScratchpad,
+    ToggleNamedScratchpad
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct ToggleWorkspaceScratchpad {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct GetContainerForWindow {
+    /// Window handle (HWND) of the window to look up
+    hwnd: isize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct IsManaged {
+    /// Window handle (HWND) of the window to check
+    hwnd: isize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct GetProcessIdForWindow {
+    /// Window handle (HWND) of the managed window to look up
+    hwnd: isize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct TraceWindow {
+    /// Window handle (HWND) of the window to trace
+    hwnd: isize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetFocusBorderColor {
+    /// Red component of the color (0-255)
+    r: u8,
+    /// Green component of the color (0-255)
+    g: u8,
+    /// Blue component of the color (0-255)
+    b: u8,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetUnfocusBorderColor {
+    /// Red component of the color (0-255)
+    r: u8,
+    /// Green component of the color (0-255)
+    g: u8,
+    /// Blue component of the color (0-255)
+    b: u8,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetWindowOpacity {
+    /// Window handle (HWND) of the window to make translucent
+    hwnd: isize,
+    /// Opacity of the window, from 0 (invisible) to 255 (opaque)
+    opacity: u8,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct UnmanageWindow {
+    /// Window handle (HWND) of the window to unmanage
+    hwnd: isize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+#[clap(setting = AppSettings::AllowNegativeNumbers)]
+struct SetMonitorWorkAreaOffset {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Pixels to add to the left edge of the work area
+    left: i32,
+    /// Pixels to add to the top edge of the work area
+    top: i32,
+    /// Pixels to add to the width of the work area
+    right: i32,
+    /// Pixels to add to the height of the work area
+    bottom: i32,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct MapMonitorToIndex {
+    /// Device name of the monitor, as shown in the output of the state command
+    name: String,
+    /// Monitor index to always assign to this monitor (zero-indexed)
+    index: usize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+#[ahk(async)]
+struct EnsureWorkspacesForAllMonitors {
+    /// Number of desired workspaces
+    workspace_count: usize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetMaxWorkspaces {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Maximum number of workspaces
+    max: usize,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct ThreeColumnRatio {
+    /// Relative width of the center column
+    main_ratio: u32,
+    /// Relative width of each of the side columns
+    secondary_ratio: u32,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetColumnsMainRatio {
+    /// Fraction of the width given to the main column when there are two columns (0.5 = equal)
+    ratio: f32,
+}
+
 macro_rules! gen_padding_subcommand_args {
     // SubCommand Pattern
     ( $( $name:ident ),+ ) => {
         $(
-            #[derive(clap::Clap, derive_ahk::AhkFunction)]
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
             pub struct $name {
                 /// Monitor index (zero-indexed)
                 monitor: usize,
@@ -177,11 +761,101 @@ gen_padding_subcommand_args! {
     WorkspacePadding
 }
 
+#[derive(Clap)]
+struct WorkspacePaddingFromEdge {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+    /// Pixels to pad from the top edge as an integer
+    #[clap(long, default_value = "0")]
+    top: i32,
+    /// Pixels to pad from the left edge as an integer
+    #[clap(long, default_value = "0")]
+    left: i32,
+    /// Pixels to pad from the right edge as an integer
+    #[clap(long, default_value = "0")]
+    right: i32,
+    /// Pixels to pad from the bottom edge as an integer
+    #[clap(long, default_value = "0")]
+    bottom: i32,
+}
+
+impl AhkFunction for WorkspacePaddingFromEdge {
+    fn generate_ahk_function() -> String {
+        String::from(
+            r"
+WorkspacePaddingFromEdge(monitor, workspace, top, left, right, bottom) {
+    Run, komorebic.exe workspace-padding-from-edge %monitor% %workspace% --top %top% --left %left% --right %right% --bottom %bottom%, , Hide
+}",
+        )
+    }
+}
+
+impl PowerShellFunction for WorkspacePaddingFromEdge {
+    fn generate_powershell_function() -> String {
+        String::from(
+            r"
+function Invoke-KomorebicWorkspacePaddingFromEdge($monitor, $workspace, $top = 0, $left = 0, $right = 0, $bottom = 0) {
+    komorebic.exe workspace-padding-from-edge $monitor $workspace --top $top --left $left --right $right --bottom $bottom
+}",
+        )
+    }
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct WorkspaceGap {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+    /// Pixels of space to leave between adjacent containers as an integer
+    size: i32,
+}
+
+macro_rules! gen_monitor_padding_subcommand_args {
+    // SubCommand Pattern
+    ( $( $name:ident ),+ ) => {
+        $(
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
+            pub struct $name {
+                /// Monitor index (zero-indexed)
+                monitor: usize,
+                /// Pixels to pad with as an integer
+                size: i32,
+            }
+        )+
+    };
+}
+
+gen_monitor_padding_subcommand_args! {
+    SetContainerPaddingForMonitor,
+    SetWorkspacePaddingForMonitor
+}
+
+macro_rules! gen_global_padding_subcommand_args {
+    // SubCommand Pattern
+    ( $( $name:ident ),+ ) => {
+        $(
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
+            pub struct $name {
+                /// Pixels to pad with as an integer
+                size: i32,
+            }
+        )+
+    };
+}
+
+gen_global_padding_subcommand_args! {
+    SetGlobalContainerPadding,
+    SetGlobalWorkspacePadding
+}
+
 macro_rules! gen_padding_adjustment_subcommand_args {
     // SubCommand Pattern
     ( $( $name:ident ),+ ) => {
         $(
-            #[derive(clap::Clap, derive_ahk::AhkFunction)]
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
             pub struct $name {
                 #[clap(arg_enum)]
                 sizing: Sizing,
@@ -201,7 +875,7 @@ macro_rules! gen_application_target_subcommand_args {
     // SubCommand Pattern
     ( $( $name:ident ),+ ) => {
         $(
-            #[derive(clap::Clap, derive_ahk::AhkFunction)]
+            #[derive(clap::Clap, derive_ahk::AhkFunction, derive_ahk::PowerShellFunction)]
             pub struct $name {
                 #[clap(arg_enum)]
                 identifier: ApplicationIdentifier,
@@ -218,7 +892,38 @@ gen_application_target_subcommand_args! {
     IdentifyTrayApplication
 }
 
-#[derive(Clap, AhkFunction)]
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct IdentifyIgnoreTitle {
+    /// Regular expression to match against window titles
+    pattern: String,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetApplicationContainerPadding {
+    #[clap(arg_enum)]
+    identifier: ApplicationIdentifier,
+    /// Identifier as a string
+    id: String,
+    /// Pixels to pad with as an integer
+    size: i32,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SuppressEventForExe {
+    #[clap(arg_enum)]
+    event: WindowManagerEventType,
+    /// Executable name of the application
+    exe: String,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct SetEventDebounce {
+    /// Number of milliseconds to ignore repeated events for (0 to disable)
+    ms: u64,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+#[ahk(async)]
 struct WorkspaceRule {
     #[clap(arg_enum)]
     identifier: ApplicationIdentifier,
@@ -230,26 +935,101 @@ struct WorkspaceRule {
     workspace: usize,
 }
 
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+#[ahk(async)]
+struct WorkspaceRuleWithCondition {
+    #[clap(arg_enum)]
+    identifier: ApplicationIdentifier,
+    /// Identifier as a string
+    id: String,
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+    /// Condition that must be met for the rule to apply (`always` or `monitor_count:<count>`)
+    condition: WorkspaceRuleCondition,
+}
+
+#[derive(ArgEnum)]
+enum Shell {
+    Bash,
+    Elvish,
+    Fish,
+    Powershell,
+    Zsh,
+}
+
+#[derive(Clap, AhkFunction, PowerShellFunction)]
+struct Completions {
+    #[clap(arg_enum)]
+    shell: Shell,
+}
+
 #[derive(Clap)]
 #[clap(author, about, version, setting = AppSettings::DeriveDisplayOrder)]
 struct Opts {
+    /// Path to the socket of the komorebi.exe instance to send messages to
+    #[clap(long, global = true)]
+    socket_path: Option<PathBuf>,
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
 
-#[derive(Clap, AhkLibrary)]
+#[derive(Clap, AhkLibrary, PowerShellLibrary)]
 enum SubCommand {
     /// Start komorebi.exe as a background process
-    Start,
+    Start(Start),
     /// Stop the komorebi.exe process and restore all hidden windows
-    Stop,
+    Stop(Stop),
+    /// Restore the layout saved by `komorebic stop --save-state`
+    RestoreState,
     /// Show a JSON representation of the current window manager state
     State,
+    /// Print a JSON representation of the window manager state every time it may have changed
+    WatchState,
+    /// Show the monitor, workspace, container and window indices of the specified window
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    GetContainerForWindow(GetContainerForWindow),
+    /// Check if the specified window is managed by komorebi
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    IsManaged(IsManaged),
+    /// Show the process and thread ids of the specified managed window
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    GetProcessIdForWindow(GetProcessIdForWindow),
+    /// Show the position and size of the focused window
+    GetFocusedWindowGeometry,
+    /// Show the monitor index, workspace index and name of the focused workspace
+    GetFocusedWorkspaceIndex,
+    /// Show the ids, device names, dimensions and DPI of all connected monitors
+    MonitorInfo,
+    /// Show the number of containers and floating windows on each workspace of each monitor
+    WorkspaceWindowCounts,
+    /// Show the full monitor, workspace, container and window hierarchy as JSON
+    DumpWindowTree,
+    /// Show the versions of komorebic.exe and the running komorebi.exe process
+    VersionDaemon,
+    /// Check that komorebi.exe is running and ready to receive commands
+    Ping(Ping),
     /// Tail komorebi.exe's process logs (cancel with Ctrl-C)
-    Log,
+    Log(Log),
+    /// Log every event received for the specified window
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    TraceWindow(TraceWindow),
+    /// Stop logging events for all traced windows
+    StopTracing,
     /// Change focus to the window in the specified direction
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     Focus(Focus),
+    /// Change focus to the managed window under the mouse cursor
+    FocusWindowUnderCursor,
+    /// Change focus to the floating window in the specified cycle direction
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    FocusFloatingWindow(FocusFloatingWindow),
+    /// Change focus back to the focused tiled window
+    FocusTiledWindow,
+    /// Change focus to the next or previous container on the focused monitor, switching workspaces if needed
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    CycleContainerAcrossWorkspaces(CycleContainerAcrossWorkspaces),
     /// Move the focused window in the specified direction
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     Move(Move),
@@ -259,25 +1039,68 @@ enum SubCommand {
     /// Resize the focused window in the specified direction
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     Resize(Resize),
+    /// Resize the focused window to the specified width and/or height
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ResizeWindowAbsolute(ResizeWindowAbsolute),
+    /// Set the minimum size that a container can be resized to
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetMinimumContainerSize(SetMinimumContainerSize),
+    /// Keep the focused container at the specified aspect ratio when it is tiled
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    LockContainerAspectRatio(LockContainerAspectRatio),
+    /// Stop keeping the focused container at a fixed aspect ratio
+    UnlockContainerAspectRatio,
     /// Unstack the focused window
     Unstack,
     /// Cycle the focused stack in the specified cycle direction
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     CycleStack(CycleStack),
+    /// Move the focused window to the specified position in the focused stack
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    MoveWindowInContainer(MoveWindowInContainer),
+    /// Split the focused stack into two containers at the specified window index
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SplitContainer(SplitContainer),
+    /// Merge the focused container with the container in the specified direction into one stack
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    MergeContainers(MergeContainers),
+    /// Send the focused container to the named scratchpad
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SendToNamedScratchpad(SendToNamedScratchpad),
+    /// Show or hide the windows in the named scratchpad
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleNamedScratchpad(ToggleNamedScratchpad),
+    /// Show or hide the windows of the specified workspace over the focused workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleWorkspaceScratchpad(ToggleWorkspaceScratchpad),
     /// Move the focused window to the specified monitor
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     MoveToMonitor(MoveToMonitor),
     /// Move the focused window to the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     MoveToWorkspace(MoveToWorkspace),
+    /// Move the focused window to the next workspace in the specified direction that has windows
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    MoveToNextNonEmptyWorkspace(MoveToNextNonEmptyWorkspace),
     /// Focus the specified monitor
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     FocusMonitor(FocusMonitor),
+    /// Focus the monitor in the specified direction
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    FocusMonitorInDirection(FocusMonitorInDirection),
+    /// Focus the next or previous monitor, wrapping around at either end
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    FocusCycleMonitor(FocusCycleMonitor),
+    /// Focus the monitor that Windows considers to be the primary display
+    FocusPrimaryMonitor,
     /// Focus the specified workspace on the focused monitor
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     FocusWorkspace(FocusWorkspace),
+    /// Focus the specified workspace on the specified monitor without changing the focused monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetFocusedMonitorWorkspace(SetFocusedMonitorWorkspace),
     /// Create and append a new workspace on the focused monitor
-    NewWorkspace,
+    NewWorkspace(NewWorkspace),
     /// Adjust container padding on the focused workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     AdjustContainerPadding(AdjustContainerPadding),
@@ -290,44 +1113,152 @@ enum SubCommand {
     /// Flip the layout on the focused workspace (BSP only)
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     FlipLayout(FlipLayout),
+    /// Restore the layout that was in use on the focused workspace before the last layout change
+    UndoLayoutChange,
+    /// Restore the layout flip that was in use on the focused workspace before the last flip
+    UndoFlipLayout,
+    /// Set the column ratios of the three-column layout on the focused workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ThreeColumnRatio(ThreeColumnRatio),
+    /// Set the width of the main column of the columns layout relative to the other columns
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetColumnsMainRatio(SetColumnsMainRatio),
     /// Promote the focused window to the top of the tree
     Promote,
+    /// Reorder the containers on the focused workspace alphabetically by executable name
+    ReorderContainersByExe,
+    /// Reorder the containers on the focused workspace alphabetically by window title
+    ReorderContainersByTitle,
+    /// Shift every container on the focused workspace one position in the specified cycle direction
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    RotateContainers(RotateContainers),
+    /// Shuffle the containers on the focused workspace into a reproducible pseudo-random order
+    ShuffleContainers,
     /// Force the retiling of all managed windows
     Retile,
     /// Create at least this many workspaces for the specified monitor
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     EnsureWorkspaces(EnsureWorkspaces),
+    /// Create at least this many workspaces for every monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    EnsureWorkspacesForAllMonitors(EnsureWorkspacesForAllMonitors),
+    /// Limit the number of workspaces that can be created for the specified monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetMaxWorkspaces(SetMaxWorkspaces),
+    /// Adjust the detected work area of the specified monitor by an offset
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetMonitorWorkAreaOffset(SetMonitorWorkAreaOffset),
+    /// Always assign the specified index to the monitor with the specified device name
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    MapMonitorToIndex(MapMonitorToIndex),
     /// Set the container padding for the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     ContainerPadding(ContainerPadding),
     /// Set the workspace padding for the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     WorkspacePadding(WorkspacePadding),
+    /// Set the workspace padding for the specified workspace as a distance from each monitor edge
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    WorkspacePaddingFromEdge(WorkspacePaddingFromEdge),
+    /// Set the gap between adjacent containers for the specified workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    WorkspaceGap(WorkspaceGap),
+    /// Set the container padding for every workspace on the specified monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetContainerPaddingForMonitor(SetContainerPaddingForMonitor),
+    /// Set the workspace padding for every workspace on the specified monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetWorkspacePaddingForMonitor(SetWorkspacePaddingForMonitor),
+    /// Set the container padding for every workspace on every monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetGlobalContainerPadding(SetGlobalContainerPadding),
+    /// Set the workspace padding for every workspace on every monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetGlobalWorkspacePadding(SetGlobalWorkspacePadding),
     /// Set the layout for the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     WorkspaceLayout(WorkspaceLayout),
+    /// Set the layout that new workspaces on the specified monitor start with
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetWorkspaceDefaultLayout(SetWorkspaceDefaultLayout),
+    /// Set the layout that new workspaces start with on monitors without a default layout
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetGlobalDefaultLayout(SetGlobalDefaultLayout),
+    /// Flip the layout for the specified workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    WorkspaceLayoutFlip(WorkspaceLayoutFlip),
     /// Enable or disable window tiling for the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     WorkspaceTiling(WorkspaceTiling),
+    /// Enable or disable mouse follows focus for the specified workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    WorkspaceMouseFollowsFocus(WorkspaceMouseFollowsFocus),
     /// Set the workspace name for the specified workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     WorkspaceName(WorkspaceName),
     /// Toggle the window manager on and off across all monitors
     TogglePause,
+    /// Toggle the window manager on and off for all workspaces on a monitor
+    TogglePauseMonitor(TogglePauseMonitor),
     /// Toggle window tiling on the focused workspace
     ToggleTiling,
+    /// Toggle between the configured padding and no padding on the focused workspace
+    ToggleGapless,
     /// Toggle floating mode for the focused window
     ToggleFloat,
+    /// Center the focused floating window on the focused monitor
+    CenterFloatingWindow,
+    /// Center the focused floating window on the specified monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    CenterFloatingWindowOnMonitor(CenterFloatingWindowOnMonitor),
+    /// Tile the focused floating window in a new container at the specified index
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    MoveFloatingWindowToTile(MoveFloatingWindowToTile),
+    /// Tile all floating windows on the focused workspace in new containers
+    TileAllFloatingWindows,
+    /// Float all windows in containers on the focused workspace
+    FloatAllContainerWindows,
+    /// Remember the current position and size of the focused window
+    MemorizeWindowPosition,
+    /// Move the focused window back to its remembered position and size
+    RestoreWindowPosition,
+    /// Keep the focused window on its current workspace regardless of any workspace rules
+    PinFocusedWindowToWorkspace,
     /// Toggle monocle mode for the focused container
     ToggleMonocle,
+    /// Cycle the monocle container through the containers on the focused workspace
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    CycleMonocle(CycleMonocle),
     /// Toggle native maximization for the focused window
     ToggleMaximize,
+    /// Toggle filling the work area with the focused container while keeping it in the layout
+    ToggleSoftMaximize,
+    /// Toggle the system-drawn border of the focused window
+    ToggleWindowBorder,
+    /// Toggle a border around whichever managed window is focused
+    ToggleFocusBorder,
+    /// Set the color of the border around the focused window (Windows 11 only)
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetFocusBorderColor(SetFocusBorderColor),
+    /// Set the color of the borders around unfocused windows (Windows 11 only)
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetUnfocusBorderColor(SetUnfocusBorderColor),
+    /// Toggle the title bars of all managed windows
+    ToggleTitleBars,
+    /// Toggle the title bar, border and window control buttons of the focused window
+    ToggleWindowDecoration,
+    /// Set the opacity of the window with the specified window handle (HWND)
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetWindowOpacity(SetWindowOpacity),
     /// Restore all hidden windows (debugging command)
     RestoreWindows,
     /// Force komorebi to manage the focused window
     Manage,
     /// Unmanage a window that was forcibly managed
     Unmanage,
+    /// Unmanage the window with the specified window handle (HWND)
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    UnmanageWindow(UnmanageWindow),
     /// Reload ~/komorebi.ahk (if it exists)
     ReloadConfiguration,
     /// Enable or disable watching of ~/komorebi.ahk (if it exists)
@@ -342,31 +1273,320 @@ enum SubCommand {
     /// Add a rule to associate an application with a workspace
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     WorkspaceRule(WorkspaceRule),
+    /// Add a rule to associate an application with a workspace when the specified condition is met
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    WorkspaceRuleWithCondition(WorkspaceRuleWithCondition),
     /// Identify an application that closes to the system tray
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     IdentifyTrayApplication(IdentifyTrayApplication),
+    /// Never manage windows with titles matching the specified regular expression
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    IdentifyIgnoreTitle(IdentifyIgnoreTitle),
+    /// Set the container padding to use for containers showing the specified application
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetApplicationContainerPadding(SetApplicationContainerPadding),
+    /// Ignore events of the specified type from windows of the specified executable
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SuppressEventForExe(SuppressEventForExe),
+    /// Only process the first and last of repeated events of the same type from the same window within the specified number of milliseconds
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    SetEventDebounce(SetEventDebounce),
     /// Enable or disable focus follows mouse for the operating system
     #[clap(setting = AppSettings::ArgRequiredElseHelp)]
     FocusFollowsMouse(FocusFollowsMouse),
     /// Toggle focus follows mouse for the operating system
     ToggleFocusFollowsMouse,
+    /// Enable or disable automatically pausing the monitor of a fullscreen window while it is shown
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleAutoFullscreenPause(ToggleAutoFullscreenPause),
+    /// Enable or disable hiding floating windows on the focused workspace when monocle is enabled
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleMonocleIncludeFloating(ToggleMonocleIncludeFloating),
+    /// Enable or disable managing any new window as soon as its title changes, regardless of its executable
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleAutoManage(ToggleAutoManage),
+    /// Enable or disable stacking new windows into an existing container with a window from the same executable
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleAutoStackByExe(ToggleAutoStackByExe),
+    /// Enable or disable binding each workspace to the Windows virtual desktop with the same index
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleVirtualDesktopIntegration(ToggleVirtualDesktopIntegration),
+    /// Enable or disable naming workspaces without a name after the executable of their focused window
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleDynamicWorkspaceNaming(ToggleDynamicWorkspaceNaming),
+    /// Enable or disable scaling padding and gaps by the DPI scale factor of each monitor
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    ToggleDpiAwarePadding(ToggleDpiAwarePadding),
     /// Generate a library of AutoHotKey helper functions
     AhkLibrary,
+    /// Generate a PowerShell module of helper functions
+    GeneratePowershellLibrary,
+    /// Generate a shell completion script for komorebic and print it to stdout
+    #[clap(setting = AppSettings::ArgRequiredElseHelp)]
+    Completions(Completions),
+}
+
+fn custom_layout_path(layout: Layout, path: Option<String>) -> Result<Layout> {
+    match layout {
+        Layout::Custom(_) => Ok(Layout::Custom(path.ok_or_else(|| {
+            anyhow!("a path to an executable must be given for the custom layout")
+        })?)),
+        Layout::Composite { .. } => Err(anyhow!(
+            "the composite layout can only be selected with the change-layout command"
+        )),
+        layout => Ok(layout),
+    }
+}
+
+fn composite_layout(
+    layout: Layout,
+    path: Option<String>,
+    primary: Option<Layout>,
+    secondary: Option<Layout>,
+) -> Result<Layout> {
+    if !matches!(layout, Layout::Composite { .. }) {
+        return custom_layout_path(layout, path);
+    }
+
+    let primary_count = path
+        .ok_or_else(|| anyhow!("a primary container count must be given for the composite layout"))?
+        .parse::<usize>()?;
+
+    let (primary, secondary) = primary.zip(secondary).ok_or_else(|| {
+        anyhow!("a primary and secondary layout must be given for the composite layout")
+    })?;
+
+    if matches!(primary, Layout::Custom(_) | Layout::Composite { .. })
+        || matches!(secondary, Layout::Custom(_) | Layout::Composite { .. })
+    {
+        return Err(anyhow!(
+            "the areas of a composite layout cannot use custom or composite layouts"
+        ));
+    }
+
+    Ok(Layout::Composite {
+        primary: Box::new(primary),
+        secondary: Box::new(secondary),
+        primary_count,
+    })
+}
+
+fn focused_monitor_idx() -> Result<usize> {
+    let state = send_query(&*SocketMessage::State.as_bytes()?)?;
+
+    let focused = state
+        .pointer("/monitors/focused")
+        .cloned()
+        .ok_or_else(|| anyhow!("could not determine the focused monitor"))?;
+
+    Ok(serde_json::from_value(focused)?)
+}
+
+fn socket_path() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("KOMOREBI_SOCKET_PATH") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let mut socket = dirs::home_dir().context("there is no home directory")?;
+    socket.push("komorebi.sock");
+    Ok(socket)
+}
+
+pub fn send_message(bytes: &[u8]) -> Result<()> {
+    let response = send_request(bytes, Duration::from_secs(5))?;
+
+    // Older versions of komorebi close the connection without sending a response
+    for line in response.lines() {
+        serde_json::from_str::<ProcessCommandResponse>(line)?.into_data()?;
+    }
+
+    Ok(())
+}
+
+fn send_request(bytes: &[u8], timeout: Duration) -> Result<String> {
+    let socket = socket_path()?;
+    let socket = socket.as_path();
+
+    let mut stream = UnixStream::connect(&socket)?;
+    stream.write_all(&*bytes)?;
+
+    // komorebi reads until the end of the stream, so our side has to be closed for writing before
+    // it will process the message and send a response back
+    stream.shutdown(Shutdown::Write)?;
+    stream.set_read_timeout(Option::from(timeout))?;
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_to_string(&mut response)
+        .map_err(|error| match error.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+                anyhow!("timed out waiting for a response from komorebi")
+            }
+            _ => error.into(),
+        })?;
+
+    Ok(response)
+}
+
+fn format_json_log_line(line: &str) -> String {
+    let value: serde_json::Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        // Anything that isn't a structured log line is printed as-is
+        Err(_) => return line.to_string(),
+    };
+
+    let field = |name: &str| value.get(name).and_then(serde_json::Value::as_str);
+
+    let mut formatted = format!(
+        "{} {:>5} {}:",
+        field("timestamp").unwrap_or_default(),
+        field("level").unwrap_or_default(),
+        field("target").unwrap_or_default()
+    );
+
+    if let Some(serde_json::Value::Object(fields)) = value.get("fields") {
+        if let Some(message) = fields.get("message").and_then(serde_json::Value::as_str) {
+            formatted.push(' ');
+            formatted.push_str(message);
+        }
+
+        for (key, value) in fields.iter().filter(|(key, _)| *key != "message") {
+            formatted.push(' ');
+            formatted.push_str(key);
+            formatted.push('=');
+            formatted.push_str(&value.to_string());
+        }
+    }
+
+    formatted
+}
+
+pub fn send_query(bytes: &[u8]) -> Result<serde_json::Value> {
+    let response = send_request(bytes, Duration::from_secs(5))?;
+    let response: ProcessCommandResponse = serde_json::from_str(response.trim())?;
+
+    Ok(response.into_data()?.unwrap_or_default())
+}
+
+fn print_query(bytes: &[u8]) -> Result<()> {
+    let data = send_query(bytes)?;
+    if !data.is_null() {
+        println!("{}", serde_json::to_string_pretty(&data)?);
+    }
+
+    Ok(())
 }
 
-pub fn send_message(bytes: &[u8]) -> Result<()> {
+fn confirm(question: &str) -> Result<bool> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(question.as_bytes())?;
+    stdout.write_all(b" [y/N] ")?;
+    stdout.flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn ping(timeout: Duration) -> Result<String> {
+    let response = send_request(&*SocketMessage::Ping.as_bytes()?, timeout)?;
+    let response: ProcessCommandResponse = serde_json::from_str(response.trim())?;
+
+    match response.into_data()? {
+        Some(data) if data.get("pong").and_then(serde_json::Value::as_bool) == Some(true) => {
+            Ok(data.to_string())
+        }
+        _ => Err(anyhow!("komorebi did not respond to the ping")),
+    }
+}
+
+fn await_daemon(timeout: Duration) -> Result<String> {
+    let started = Instant::now();
+    let mut backoff = Duration::from_millis(50);
+
+    loop {
+        // The socket file is created before komorebi has finished initializing, so we wait for a
+        // response to a ping instead of just checking that the socket exists
+        let elapsed = started.elapsed();
+        if let Ok(response) = ping(timeout.saturating_sub(elapsed)) {
+            return Ok(response);
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            return Err(anyhow!(
+                "komorebi was not ready after waiting for {}ms",
+                timeout.as_millis()
+            ));
+        }
+
+        thread::sleep(backoff.min(timeout.saturating_sub(elapsed)));
+        backoff *= 2;
+    }
+}
+
+fn send_message_from_stdin() -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let message: SocketMessage = input.trim().parse()?;
+    if message.is_query() {
+        print_query(&*message.as_bytes()?)?;
+    } else {
+        send_message(&*message.as_bytes()?)?;
+    }
+
+    Ok(())
+}
+
+fn watch_state() -> Result<()> {
     let mut socket = dirs::home_dir().context("there is no home directory")?;
-    socket.push("komorebi.sock");
-    let socket = socket.as_path();
+    // Each watcher needs its own socket so that more than one can run at the same time
+    socket.push(format!("komorebic-{}.sock", std::process::id()));
 
-    let mut stream = UnixStream::connect(&socket)?;
-    Ok(stream.write_all(&*bytes)?)
+    // Doing this because ::exists() doesn't work reliably on Windows via IntelliJ
+    if let Err(error) = std::fs::remove_file(&socket) {
+        if error.kind() != ErrorKind::NotFound {
+            return Err(error.into());
+        }
+    }
+
+    let listener = UnixListener::bind(&socket)?;
+    send_message(
+        &*SocketMessage::AddSubscriber(
+            socket
+                .to_str()
+                .context("could not convert the socket path to a string")?
+                .to_string(),
+        )
+        .as_bytes()?,
+    )?;
+
+    for client in listener.incoming() {
+        let mut state = String::new();
+        BufReader::new(client?).read_to_string(&mut state)?;
+        println!("{}", state.trim());
+    }
+
+    Ok(())
 }
 
 #[allow(clippy::too_many_lines)]
 fn main() -> Result<()> {
+    // Passing "--" skips argument parsing and sends a JSON-encoded SocketMessage from stdin
+    if std::env::args().nth(1).as_deref() == Some("--") {
+        return send_message_from_stdin();
+    }
+
     let opts: Opts = Opts::parse();
 
+    // Setting this for the whole process means that it is also inherited by komorebi.exe when
+    // it is launched with the start command
+    if let Some(socket_path) = &opts.socket_path {
+        std::env::set_var("KOMOREBI_SOCKET_PATH", socket_path);
+    }
+
     match opts.subcmd {
         SubCommand::AhkLibrary => {
             let mut library = dirs::home_dir().context("there is no home directory")?;
@@ -392,24 +1612,109 @@ fn main() -> Result<()> {
 
             println!("\n#Include %A_ScriptDir%\\komorebic.lib.ahk");
         }
-        SubCommand::Log => {
-            let mut color_log = std::env::temp_dir();
-            color_log.push("komorebi.log");
-            let file = TailedFile::new(File::open(color_log)?);
+        SubCommand::GeneratePowershellLibrary => {
+            let mut library = dirs::home_dir().context("there is no home directory")?;
+            library.push("komorebic.psm1");
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(library.clone())?;
+
+            file.write_all(SubCommand::generate_powershell_library().as_bytes())?;
+
+            println!(
+                "\nPowerShell helper module for komorebic written to {}",
+                library
+                    .to_str()
+                    .context("could not find the path to the generated powershell module file")?
+            );
+
+            println!("\nYou can import the module in your PowerShell profile with this line:");
+
+            println!("\nImport-Module $Env:UserProfile\\komorebic.psm1");
+        }
+        SubCommand::Completions(arg) => {
+            let mut app = Opts::into_app();
+            let mut stdout = std::io::stdout();
+
+            match arg.shell {
+                Shell::Bash => generate::<Bash, _>(&mut app, "komorebic", &mut stdout),
+                Shell::Elvish => generate::<Elvish, _>(&mut app, "komorebic", &mut stdout),
+                Shell::Fish => generate::<Fish, _>(&mut app, "komorebic", &mut stdout),
+                Shell::Powershell => generate::<PowerShell, _>(&mut app, "komorebic", &mut stdout),
+                Shell::Zsh => generate::<Zsh, _>(&mut app, "komorebic", &mut stdout),
+            }
+        }
+        SubCommand::Log(arg) => {
+            let log = if arg.json {
+                let mut json_log = dirs::home_dir().context("there is no home directory")?;
+                json_log.push("komorebi.json.log");
+                json_log
+            } else if arg.no_color {
+                let mut log = dirs::home_dir().context("there is no home directory")?;
+                log.push("komorebi.log");
+                log
+            } else {
+                let mut color_log = std::env::temp_dir();
+                color_log.push("komorebi.log");
+                color_log
+            };
+
+            let file = TailedFile::new(File::open(log)?);
             let locked = file.lock();
             for line in locked.lines() {
-                println!("{}", line?);
+                let line = line?;
+                if arg.json {
+                    println!("{}", format_json_log_line(&line));
+                } else {
+                    println!("{}", line);
+                }
             }
         }
         SubCommand::Focus(arg) => {
             send_message(&*SocketMessage::FocusWindow(arg.operation_direction).as_bytes()?)?;
         }
+        SubCommand::FocusWindowUnderCursor => {
+            send_message(&*SocketMessage::FocusWindowUnderCursor.as_bytes()?)?;
+        }
+        SubCommand::FocusFloatingWindow(arg) => {
+            send_message(&*SocketMessage::FocusFloatingWindow(arg.cycle_direction).as_bytes()?)?;
+        }
+        SubCommand::FocusTiledWindow => {
+            send_message(&*SocketMessage::FocusTiledWindow.as_bytes()?)?;
+        }
+        SubCommand::CycleContainerAcrossWorkspaces(arg) => {
+            send_message(
+                &*SocketMessage::CycleContainerAcrossWorkspaces(arg.cycle_direction).as_bytes()?,
+            )?;
+        }
         SubCommand::Promote => {
             send_message(&*SocketMessage::Promote.as_bytes()?)?;
         }
+        SubCommand::ReorderContainersByExe => {
+            send_message(&*SocketMessage::ReorderContainersByExe.as_bytes()?)?;
+        }
+        SubCommand::ReorderContainersByTitle => {
+            send_message(&*SocketMessage::ReorderContainersByTitle.as_bytes()?)?;
+        }
+        SubCommand::RotateContainers(arg) => {
+            send_message(&*SocketMessage::RotateContainers(arg.cycle_direction).as_bytes()?)?;
+        }
+        SubCommand::ShuffleContainers => {
+            send_message(&*SocketMessage::ShuffleContainers.as_bytes()?)?;
+        }
         SubCommand::TogglePause => {
             send_message(&*SocketMessage::TogglePause.as_bytes()?)?;
         }
+        SubCommand::TogglePauseMonitor(arg) => {
+            let monitor_idx = match arg.monitor {
+                Some(idx) => idx,
+                None => focused_monitor_idx()?,
+            };
+
+            send_message(&*SocketMessage::TogglePauseMonitor(monitor_idx).as_bytes()?)?;
+        }
         SubCommand::Retile => {
             send_message(&*SocketMessage::Retile.as_bytes()?)?;
         }
@@ -422,18 +1727,61 @@ fn main() -> Result<()> {
         SubCommand::MoveToWorkspace(arg) => {
             send_message(&*SocketMessage::MoveContainerToWorkspaceNumber(arg.target).as_bytes()?)?;
         }
+        SubCommand::MoveToNextNonEmptyWorkspace(arg) => {
+            send_message(
+                &*SocketMessage::MoveContainerToNextNonEmptyWorkspace(arg.cycle_direction)
+                    .as_bytes()?,
+            )?;
+        }
         SubCommand::ContainerPadding(arg) => {
             send_message(
                 &*SocketMessage::ContainerPadding(arg.monitor, arg.workspace, arg.size)
                     .as_bytes()?,
             )?;
         }
+        SubCommand::WorkspaceGap(arg) => {
+            send_message(
+                &*SocketMessage::SetWorkspaceGap(arg.monitor, arg.workspace, arg.size)
+                    .as_bytes()?,
+            )?;
+        }
         SubCommand::WorkspacePadding(arg) => {
             send_message(
                 &*SocketMessage::WorkspacePadding(arg.monitor, arg.workspace, arg.size)
                     .as_bytes()?,
             )?;
         }
+        SubCommand::WorkspacePaddingFromEdge(arg) => {
+            send_message(
+                &*SocketMessage::SetWorkspacePaddingFromEdge(
+                    arg.monitor,
+                    arg.workspace,
+                    Rect {
+                        left: arg.left,
+                        top: arg.top,
+                        right: arg.right,
+                        bottom: arg.bottom,
+                    },
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::SetContainerPaddingForMonitor(arg) => {
+            send_message(
+                &*SocketMessage::SetContainerPaddingForMonitor(arg.monitor, arg.size).as_bytes()?,
+            )?;
+        }
+        SubCommand::SetWorkspacePaddingForMonitor(arg) => {
+            send_message(
+                &*SocketMessage::SetWorkspacePaddingForMonitor(arg.monitor, arg.size).as_bytes()?,
+            )?;
+        }
+        SubCommand::SetGlobalContainerPadding(arg) => {
+            send_message(&*SocketMessage::SetGlobalContainerPadding(arg.size).as_bytes()?)?;
+        }
+        SubCommand::SetGlobalWorkspacePadding(arg) => {
+            send_message(&*SocketMessage::SetGlobalWorkspacePadding(arg.size).as_bytes()?)?;
+        }
         SubCommand::AdjustWorkspacePadding(arg) => {
             send_message(
                 &*SocketMessage::AdjustWorkspacePadding(arg.sizing, arg.adjustment).as_bytes()?,
@@ -450,18 +1798,97 @@ fn main() -> Result<()> {
         SubCommand::ToggleTiling => {
             send_message(&*SocketMessage::ToggleTiling.as_bytes()?)?;
         }
+        SubCommand::ToggleGapless => {
+            send_message(&*SocketMessage::ToggleGapless.as_bytes()?)?;
+        }
         SubCommand::ToggleFloat => {
             send_message(&*SocketMessage::ToggleFloat.as_bytes()?)?;
         }
+        SubCommand::CenterFloatingWindow => {
+            send_message(&*SocketMessage::CenterFloatingWindow.as_bytes()?)?;
+        }
+        SubCommand::MemorizeWindowPosition => {
+            send_message(&*SocketMessage::MemorizeWindowPosition.as_bytes()?)?;
+        }
+        SubCommand::RestoreWindowPosition => {
+            send_message(&*SocketMessage::RestoreWindowPosition.as_bytes()?)?;
+        }
+        SubCommand::PinFocusedWindowToWorkspace => {
+            send_message(&*SocketMessage::PinFocusedWindowToWorkspace.as_bytes()?)?;
+        }
+        SubCommand::CenterFloatingWindowOnMonitor(arg) => {
+            send_message(&*SocketMessage::CenterFloatingWindowOnMonitor(arg.target).as_bytes()?)?;
+        }
+        SubCommand::MoveFloatingWindowToTile(arg) => {
+            send_message(&*SocketMessage::MoveFloatingWindowToTile(arg.target).as_bytes()?)?;
+        }
+        SubCommand::TileAllFloatingWindows => {
+            send_message(&*SocketMessage::TileAllFloatingWindows.as_bytes()?)?;
+        }
+        SubCommand::FloatAllContainerWindows => {
+            send_message(&*SocketMessage::FloatAllContainerWindows.as_bytes()?)?;
+        }
         SubCommand::ToggleMonocle => {
             send_message(&*SocketMessage::ToggleMonocle.as_bytes()?)?;
         }
+        SubCommand::CycleMonocle(arg) => {
+            send_message(&*SocketMessage::CycleMonocle(arg.cycle_direction).as_bytes()?)?;
+        }
         SubCommand::ToggleMaximize => {
             send_message(&*SocketMessage::ToggleMaximize.as_bytes()?)?;
         }
+        SubCommand::ToggleSoftMaximize => {
+            send_message(&*SocketMessage::ToggleSoftMaximize.as_bytes()?)?;
+        }
+        SubCommand::ToggleWindowBorder => {
+            send_message(&*SocketMessage::ToggleWindowBorder.as_bytes()?)?;
+        }
+        SubCommand::ToggleFocusBorder => {
+            send_message(&*SocketMessage::ToggleFocusBorder.as_bytes()?)?;
+        }
+        SubCommand::SetFocusBorderColor(arg) => {
+            send_message(&*SocketMessage::SetFocusBorderColor(arg.r, arg.g, arg.b).as_bytes()?)?;
+        }
+        SubCommand::SetUnfocusBorderColor(arg) => {
+            send_message(&*SocketMessage::SetUnfocusBorderColor(arg.r, arg.g, arg.b).as_bytes()?)?;
+        }
+        SubCommand::ToggleTitleBars => {
+            send_message(&*SocketMessage::ToggleTitleBars.as_bytes()?)?;
+        }
+        SubCommand::ToggleWindowDecoration => {
+            send_message(&*SocketMessage::ToggleWindowDecoration.as_bytes()?)?;
+        }
+        SubCommand::SetWindowOpacity(arg) => {
+            send_message(&*SocketMessage::SetWindowOpacity(arg.hwnd, arg.opacity).as_bytes()?)?;
+        }
         SubCommand::WorkspaceLayout(arg) => {
             send_message(
-                &*SocketMessage::WorkspaceLayout(arg.monitor, arg.workspace, arg.value)
+                &*SocketMessage::WorkspaceLayout(
+                    arg.monitor,
+                    arg.workspace,
+                    custom_layout_path(arg.value, arg.path)?,
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::SetWorkspaceDefaultLayout(arg) => {
+            send_message(
+                &*SocketMessage::SetWorkspaceDefaultLayout(
+                    arg.monitor,
+                    custom_layout_path(arg.value, arg.path)?,
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::SetGlobalDefaultLayout(arg) => {
+            send_message(
+                &*SocketMessage::SetGlobalDefaultLayout(custom_layout_path(arg.value, arg.path)?)
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::WorkspaceLayoutFlip(arg) => {
+            send_message(
+                &*SocketMessage::WorkspaceLayoutFlip(arg.monitor, arg.workspace, arg.value)
                     .as_bytes()?,
             )?;
         }
@@ -471,7 +1898,36 @@ fn main() -> Result<()> {
                     .as_bytes()?,
             )?;
         }
-        SubCommand::Start => {
+        SubCommand::WorkspaceMouseFollowsFocus(arg) => {
+            send_message(
+                &*SocketMessage::WorkspaceMouseFollowsFocus(
+                    arg.monitor,
+                    arg.workspace,
+                    arg.value.into(),
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::Start(arg) => {
+            // The daemon is started by Start-Process, which passes our environment along to it
+            if let Some(config) = arg.config {
+                let config = if config.is_absolute() {
+                    config
+                } else {
+                    std::env::current_dir()?.join(config)
+                };
+
+                if !config.exists() {
+                    return Err(anyhow!("the specified configuration file does not exist"));
+                }
+
+                std::env::set_var("KOMOREBI_CONFIG", config.as_os_str());
+            }
+
+            if arg.json_log {
+                std::env::set_var("KOMOREBI_JSON_LOG", "1");
+            }
+
             let mut buf: PathBuf;
 
             // The komorebi.ps1 shim will only exist in the Path if installed by Scoop
@@ -508,9 +1964,54 @@ fn main() -> Result<()> {
                     println!("Error: {}", error);
                 }
             }
+
+            if arg.await_ready {
+                await_daemon(Duration::from_secs(arg.timeout))?;
+            }
+
+            let mut saved_state = dirs::home_dir().context("there is no home directory")?;
+            saved_state.push("komorebi.state.json");
+
+            if saved_state.exists() {
+                // There is nobody to answer the question when komorebic is run without a terminal,
+                // for example from the AHK library, so we only print a hint in that case
+                let restore = if arg.restore_state {
+                    true
+                } else if atty::is(atty::Stream::Stdin) {
+                    confirm(&format!(
+                        "Found a layout saved by `komorebic stop --save-state` at {}, would you like to restore it?",
+                        saved_state.display()
+                    ))?
+                } else {
+                    println!(
+                        "Found a layout saved by `komorebic stop --save-state` at {}, run `komorebic restore-state` to restore it",
+                        saved_state.display()
+                    );
+
+                    false
+                };
+
+                if restore {
+                    if !arg.await_ready {
+                        await_daemon(Duration::from_secs(arg.timeout))?;
+                    }
+
+                    send_message(&*SocketMessage::RestoreState.as_bytes()?)?;
+                }
+            }
         }
-        SubCommand::Stop => {
-            send_message(&*SocketMessage::Stop.as_bytes()?)?;
+        SubCommand::Ping(arg) => {
+            println!("{}", await_daemon(Duration::from_millis(arg.timeout))?);
+        }
+        SubCommand::Stop(arg) => {
+            if arg.save_state {
+                send_message(&*SocketMessage::StopWithSaveState.as_bytes()?)?;
+            } else {
+                send_message(&*SocketMessage::Stop.as_bytes()?)?;
+            }
+        }
+        SubCommand::RestoreState => {
+            send_message(&*SocketMessage::RestoreState.as_bytes()?)?;
         }
         SubCommand::FloatRule(arg) => {
             send_message(&*SocketMessage::FloatRule(arg.identifier, arg.id).as_bytes()?)?;
@@ -520,8 +2021,23 @@ fn main() -> Result<()> {
         }
         SubCommand::WorkspaceRule(arg) => {
             send_message(
-                &*SocketMessage::WorkspaceRule(arg.identifier, arg.id, arg.monitor, arg.workspace)
-                    .as_bytes()?,
+                &*SocketMessage::WorkspaceRule(
+                    arg.identifier,
+                    arg.id,
+                    MonitorWorkspaceMapping::new(arg.monitor, arg.workspace),
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::WorkspaceRuleWithCondition(arg) => {
+            send_message(
+                &*SocketMessage::WorkspaceRuleWithCondition(
+                    arg.identifier,
+                    arg.id,
+                    MonitorWorkspaceMapping::new(arg.monitor, arg.workspace),
+                    arg.condition,
+                )
+                .as_bytes()?,
             )?;
         }
         SubCommand::Stack(arg) => {
@@ -533,20 +2049,85 @@ fn main() -> Result<()> {
         SubCommand::CycleStack(arg) => {
             send_message(&*SocketMessage::CycleStack(arg.cycle_direction).as_bytes()?)?;
         }
+        SubCommand::MoveWindowInContainer(arg) => {
+            send_message(
+                &*SocketMessage::MoveWindowInContainerToPosition(arg.position).as_bytes()?,
+            )?;
+        }
+        SubCommand::SplitContainer(arg) => {
+            send_message(&*SocketMessage::SplitContainer(arg.at).as_bytes()?)?;
+        }
+        SubCommand::MergeContainers(arg) => {
+            send_message(
+                &*SocketMessage::MergeContainersInDirection(arg.operation_direction).as_bytes()?,
+            )?;
+        }
+        SubCommand::SendToNamedScratchpad(arg) => {
+            send_message(&*SocketMessage::SendToNamedScratchpad(arg.name).as_bytes()?)?;
+        }
+        SubCommand::ToggleNamedScratchpad(arg) => {
+            send_message(&*SocketMessage::ToggleNamedScratchpad(arg.name).as_bytes()?)?;
+        }
+        SubCommand::ToggleWorkspaceScratchpad(arg) => {
+            send_message(
+                &*SocketMessage::ToggleWorkspaceScratchpad(arg.monitor, arg.workspace)
+                    .as_bytes()?,
+            )?;
+        }
         SubCommand::ChangeLayout(arg) => {
-            send_message(&*SocketMessage::ChangeLayout(arg.layout).as_bytes()?)?;
+            send_message(
+                &*SocketMessage::ChangeLayout(composite_layout(
+                    arg.layout,
+                    arg.path,
+                    arg.primary,
+                    arg.secondary,
+                )?)
+                .as_bytes()?,
+            )?;
         }
         SubCommand::FlipLayout(arg) => {
             send_message(&*SocketMessage::FlipLayout(arg.flip).as_bytes()?)?;
         }
+        SubCommand::UndoLayoutChange => {
+            send_message(&*SocketMessage::UndoLayoutChange.as_bytes()?)?;
+        }
+        SubCommand::UndoFlipLayout => {
+            send_message(&*SocketMessage::UndoFlipLayout.as_bytes()?)?;
+        }
+        SubCommand::ThreeColumnRatio(arg) => {
+            send_message(
+                &*SocketMessage::SetThreeColumnRatio(arg.main_ratio, arg.secondary_ratio)
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::SetColumnsMainRatio(arg) => {
+            send_message(&*SocketMessage::SetColumnsMainRatio(arg.ratio).as_bytes()?)?;
+        }
         SubCommand::FocusMonitor(arg) => {
             send_message(&*SocketMessage::FocusMonitorNumber(arg.target).as_bytes()?)?;
         }
+        SubCommand::FocusMonitorInDirection(arg) => {
+            send_message(
+                &*SocketMessage::FocusMonitorInDirection(arg.operation_direction).as_bytes()?,
+            )?;
+        }
+        SubCommand::FocusCycleMonitor(arg) => {
+            send_message(&*SocketMessage::FocusCycleMonitor(arg.cycle_direction).as_bytes()?)?;
+        }
+        SubCommand::FocusPrimaryMonitor => {
+            send_message(&*SocketMessage::FocusPrimaryMonitor.as_bytes()?)?;
+        }
         SubCommand::FocusWorkspace(arg) => {
             send_message(&*SocketMessage::FocusWorkspaceNumber(arg.target).as_bytes()?)?;
         }
-        SubCommand::NewWorkspace => {
-            send_message(&*SocketMessage::NewWorkspace.as_bytes()?)?;
+        SubCommand::SetFocusedMonitorWorkspace(arg) => {
+            send_message(
+                &*SocketMessage::SetFocusedMonitorWorkspace(arg.monitor, arg.workspace)
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::NewWorkspace(arg) => {
+            send_message(&*SocketMessage::NewNamedWorkspace(arg.name).as_bytes()?)?;
         }
         SubCommand::WorkspaceName(name) => {
             send_message(
@@ -560,38 +2141,95 @@ fn main() -> Result<()> {
                     .as_bytes()?,
             )?;
         }
+        SubCommand::SetMonitorWorkAreaOffset(arg) => {
+            send_message(
+                &*SocketMessage::SetMonitorWorkAreaOffset(
+                    arg.monitor,
+                    Rect {
+                        left: arg.left,
+                        top: arg.top,
+                        right: arg.right,
+                        bottom: arg.bottom,
+                    },
+                )
+                .as_bytes()?,
+            )?;
+        }
+        SubCommand::MapMonitorToIndex(arg) => {
+            send_message(&*SocketMessage::MapMonitorToIndex(arg.name, arg.index).as_bytes()?)?;
+        }
+        SubCommand::EnsureWorkspacesForAllMonitors(workspaces) => {
+            send_message(
+                &*SocketMessage::EnsureWorkspacesForAllMonitors(workspaces.workspace_count)
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::SetMaxWorkspaces(arg) => {
+            send_message(&*SocketMessage::SetMaxWorkspaces(arg.monitor, arg.max).as_bytes()?)?;
+        }
         SubCommand::State => {
-            let home = dirs::home_dir().context("there is no home directory")?;
-            let mut socket = home;
-            socket.push("komorebic.sock");
-            let socket = socket.as_path();
-
-            match std::fs::remove_file(&socket) {
-                Ok(_) => {}
-                Err(error) => match error.kind() {
-                    // Doing this because ::exists() doesn't work reliably on Windows via IntelliJ
-                    ErrorKind::NotFound => {}
-                    _ => {
-                        return Err(error.into());
-                    }
-                },
-            };
+            print_query(&*SocketMessage::State.as_bytes()?)?;
+        }
+        SubCommand::WatchState => {
+            watch_state()?;
+        }
+        SubCommand::GetContainerForWindow(arg) => {
+            let response =
+                send_query(&*SocketMessage::GetContainerForWindow(arg.hwnd).as_bytes()?)?;
+            if response.is_null() {
+                return Err(anyhow!("this window is not managed by komorebi"));
+            }
 
-            send_message(&*SocketMessage::State.as_bytes()?)?;
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+        SubCommand::IsManaged(arg) => {
+            let status = send_query(&*SocketMessage::IsManaged(arg.hwnd).as_bytes()?)?;
 
-            let listener = UnixListener::bind(&socket)?;
-            match listener.accept() {
-                Ok(incoming) => {
-                    let stream = BufReader::new(incoming.0);
-                    for line in stream.lines() {
-                        println!("{}", line?);
-                    }
+            println!(
+                "{}",
+                status
+                    .get("managed")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or_default()
+            );
+        }
+        SubCommand::GetProcessIdForWindow(arg) => {
+            let response =
+                send_query(&*SocketMessage::GetProcessIdForWindow(arg.hwnd).as_bytes()?)?;
+            if response.is_null() {
+                return Err(anyhow!("this window is not managed by komorebi"));
+            }
 
-                    return Ok(());
-                }
-                Err(error) => {
-                    panic!("{}", error);
-                }
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+        SubCommand::GetFocusedWindowGeometry => {
+            print_query(&*SocketMessage::GetFocusedWindowGeometry.as_bytes()?)?;
+        }
+        SubCommand::GetFocusedWorkspaceIndex => {
+            print_query(&*SocketMessage::GetFocusedWorkspaceIndex.as_bytes()?)?;
+        }
+        SubCommand::MonitorInfo => {
+            print_query(&*SocketMessage::MonitorInfo.as_bytes()?)?;
+        }
+        SubCommand::WorkspaceWindowCounts => {
+            print_query(&*SocketMessage::WorkspaceWindowCounts.as_bytes()?)?;
+        }
+        SubCommand::DumpWindowTree => {
+            print_query(&*SocketMessage::DumpWindowTree.as_bytes()?)?;
+        }
+        SubCommand::VersionDaemon => {
+            let client_version = env!("CARGO_PKG_VERSION");
+            let daemon_version: String =
+                serde_json::from_value(send_query(&*SocketMessage::Version.as_bytes()?)?)?;
+
+            println!("komorebic {}", client_version);
+            println!("komorebi {}", daemon_version);
+
+            if client_version != daemon_version {
+                eprintln!(
+                    "warning: komorebic {} does not match the running komorebi {}, some commands may not work as expected",
+                    client_version, daemon_version
+                );
             }
         }
         SubCommand::RestoreWindows => {
@@ -609,6 +2247,26 @@ fn main() -> Result<()> {
         SubCommand::Resize(resize) => {
             send_message(&*SocketMessage::ResizeWindow(resize.edge, resize.sizing).as_bytes()?)?;
         }
+        SubCommand::ResizeWindowAbsolute(arg) => {
+            if arg.width.is_none() && arg.height.is_none() {
+                return Err(anyhow!("at least one of --width or --height must be given"));
+            }
+
+            send_message(&*SocketMessage::ResizeWindowAbsolute(arg.width, arg.height).as_bytes()?)?;
+        }
+        SubCommand::SetMinimumContainerSize(arg) => {
+            send_message(
+                &*SocketMessage::SetMinimumContainerSize(arg.width, arg.height).as_bytes()?,
+            )?;
+        }
+        SubCommand::LockContainerAspectRatio(arg) => {
+            send_message(
+                &*SocketMessage::LockContainerAspectRatio(arg.width, arg.height).as_bytes()?,
+            )?;
+        }
+        SubCommand::UnlockContainerAspectRatio => {
+            send_message(&*SocketMessage::UnlockContainerAspectRatio.as_bytes()?)?;
+        }
         SubCommand::FocusFollowsMouse(arg) => {
             let enable = match arg.boolean_state {
                 BooleanState::Enable => true,
@@ -617,6 +2275,48 @@ fn main() -> Result<()> {
 
             send_message(&*SocketMessage::FocusFollowsMouse(enable).as_bytes()?)?;
         }
+        SubCommand::ToggleAutoFullscreenPause(arg) => {
+            send_message(
+                &*SocketMessage::ToggleAutoFullscreenPause(arg.boolean_state.into()).as_bytes()?,
+            )?;
+        }
+        SubCommand::ToggleMonocleIncludeFloating(arg) => {
+            send_message(
+                &*SocketMessage::ToggleMonocleIncludeFloating(arg.boolean_state.into())
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::TraceWindow(arg) => {
+            send_message(&*SocketMessage::TraceWindow(arg.hwnd).as_bytes()?)?;
+        }
+        SubCommand::StopTracing => {
+            send_message(&*SocketMessage::StopTracing.as_bytes()?)?;
+        }
+        SubCommand::ToggleAutoManage(arg) => {
+            send_message(&*SocketMessage::ToggleAutoManage(arg.boolean_state.into()).as_bytes()?)?;
+        }
+        SubCommand::ToggleAutoStackByExe(arg) => {
+            send_message(
+                &*SocketMessage::ToggleAutoStackByExe(arg.boolean_state.into()).as_bytes()?,
+            )?;
+        }
+        SubCommand::ToggleVirtualDesktopIntegration(arg) => {
+            send_message(
+                &*SocketMessage::EnableVirtualDesktopIntegration(arg.boolean_state.into())
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::ToggleDynamicWorkspaceNaming(arg) => {
+            send_message(
+                &*SocketMessage::ToggleDynamicWorkspaceNaming(arg.boolean_state.into())
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::ToggleDpiAwarePadding(arg) => {
+            send_message(
+                &*SocketMessage::ToggleDpiAwarePadding(arg.boolean_state.into()).as_bytes()?,
+            )?;
+        }
         SubCommand::ReloadConfiguration => {
             send_message(&*SocketMessage::ReloadConfiguration.as_bytes()?)?;
         }
@@ -633,12 +2333,30 @@ fn main() -> Result<()> {
                     .as_bytes()?,
             )?;
         }
+        SubCommand::IdentifyIgnoreTitle(arg) => {
+            send_message(&*SocketMessage::IdentifyIgnoreTitle(arg.pattern).as_bytes()?)?;
+        }
+        SubCommand::SetApplicationContainerPadding(arg) => {
+            send_message(
+                &*SocketMessage::SetApplicationContainerPadding(arg.identifier, arg.id, arg.size)
+                    .as_bytes()?,
+            )?;
+        }
+        SubCommand::SuppressEventForExe(arg) => {
+            send_message(&*SocketMessage::SuppressEventForExe(arg.event, arg.exe).as_bytes()?)?;
+        }
+        SubCommand::SetEventDebounce(arg) => {
+            send_message(&*SocketMessage::SetEventDebounce(arg.ms).as_bytes()?)?;
+        }
         SubCommand::Manage => {
             send_message(&*SocketMessage::ManageFocusedWindow.as_bytes()?)?;
         }
         SubCommand::Unmanage => {
             send_message(&*SocketMessage::UnmanageFocusedWindow.as_bytes()?)?;
         }
+        SubCommand::UnmanageWindow(arg) => {
+            send_message(&*SocketMessage::UnmanageWindow(arg.hwnd).as_bytes()?)?;
+        }
     }
 
     Ok(())